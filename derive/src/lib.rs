@@ -0,0 +1,300 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Derive macros for `libsa`
+//!
+//! These are re-exported by `libsa` when its `derive` feature is enabled, and should be used
+//! through it.
+
+#![warn(clippy::cargo, clippy::pedantic)]
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, Ident, Path};
+
+/// Derives `libsa::endian::EndianStruct` for a structure of endian types
+///
+/// The structure must be `#[repr(C)]` (optionally `packed`) or `#[repr(transparent)]`,
+/// implement `Copy`, and have no padding. Every field must implement `EndianStruct`, which
+/// includes the endian types, primitives, arrays of these, and other structures deriving
+/// `EndianStruct`.
+///
+/// A native-endian counterpart named `<Name>Native` is generated, along with inherent
+/// `to_native()`, `from_native()` and `from_bytes()` methods. The following options may be
+/// given in an `#[endian(...)]` attribute:
+///
+/// - `native = Ident`: the name of the native structure
+/// - `crate = path`: the path to `libsa`, if it has been renamed
+///
+/// ```
+/// use libsa::endian::{u16_be, u32_le, EndianStruct, U32Be};
+///
+/// #[derive(Clone, Copy, EndianStruct)]
+/// #[repr(C)]
+/// struct Header {
+///     magic: u32_le,
+///     version: u16_be,
+///     flags: u16_be,
+/// }
+///
+/// #[derive(Clone, Copy, EndianStruct)]
+/// #[repr(C, packed)]
+/// struct Record {
+///     kind: u8,
+///     len: U32Be,
+///     offset: u32_le,
+/// }
+///
+/// #[derive(Clone, Copy, EndianStruct)]
+/// #[repr(C)]
+/// #[endian(native = Image)]
+/// struct RawImage {
+///     header: Header,
+///     sizes: [u32_le; 2],
+/// }
+///
+/// let header = HeaderNative { magic: 0xfeed_face, version: 3, flags: 0x8001 };
+/// assert_eq!(Header::from_native(header).to_native(), header);
+///
+/// let record = RecordNative { kind: 1, len: 0x0102_0304, offset: 0x0506_0708 };
+/// assert_eq!(Record::from_native(record).to_native(), record);
+/// assert_eq!(core::mem::size_of::<Record>(), 9);
+///
+/// #[repr(C, align(8))]
+/// struct Aligned([u8; 24]);
+///
+/// let mut bytes = Aligned([0; 24]);
+/// bytes.0[..4].copy_from_slice(&0xfeed_face_u32.to_le_bytes());
+/// bytes.0[4..6].copy_from_slice(&3_u16.to_be_bytes());
+/// bytes.0[12..16].copy_from_slice(&7_u32.to_le_bytes());
+///
+/// let image = RawImage::from_bytes(&bytes.0).unwrap().to_native();
+/// assert_eq!(image.header, HeaderNative { magic: 0xfeed_face, version: 3, flags: 0 });
+/// assert_eq!(image.sizes, [0, 7]);
+/// assert_eq!(RawImage::from_native(image).to_native(), image);
+///
+/// // Too short
+/// assert!(RawImage::from_bytes(&bytes.0[..15]).is_none());
+/// // Misaligned
+/// assert!(RawImage::from_bytes(&bytes.0[2..]).is_none());
+/// // Packed structures have no alignment requirement
+/// assert!(Record::from_bytes(&bytes.0[1..10]).is_some());
+/// assert!(Record::from_bytes(&bytes.0[1..9]).is_none());
+/// ```
+///
+/// Structures with padding are rejected:
+///
+/// ```compile_fail
+/// use libsa::endian::{u16_be, u32_be, EndianStruct};
+///
+/// #[derive(Clone, Copy, EndianStruct)]
+/// #[repr(C)]
+/// struct Padded {
+///     a: u16_be,
+///     b: u32_be,
+/// }
+/// ```
+///
+/// As are structures without a defined layout:
+///
+/// ```compile_fail
+/// use libsa::endian::{u32_be, EndianStruct};
+///
+/// #[derive(Clone, Copy, EndianStruct)]
+/// struct Unordered {
+///     a: u32_be,
+///     b: u32_be,
+/// }
+/// ```
+#[proc_macro_derive(EndianStruct, attributes(endian))]
+pub fn derive_endian_struct(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    endian_struct(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct Options {
+    native: Ident,
+    krate: Path,
+}
+
+impl Options {
+    fn parse(input: &DeriveInput) -> syn::Result<Options> {
+        let mut native = None;
+        let mut krate = None;
+
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("endian"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("native") {
+                    native = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("crate") {
+                    krate = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("unknown `endian` option"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(Options {
+            native: native.unwrap_or_else(|| format_ident!("{}Native", input.ident)),
+            krate: krate.unwrap_or_else(|| syn::parse_quote!(::libsa)),
+        })
+    }
+}
+
+/// Checks that the structure has a well-defined layout
+fn check_repr(input: &DeriveInput) -> syn::Result<()> {
+    let mut defined = false;
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") || meta.path.is_ident("transparent") {
+                defined = true;
+            } else if meta.input.peek(syn::token::Paren) {
+                // Skip the arguments of e.g. `align(N)` and `packed(N)`.
+                let _ = meta.input.parse::<proc_macro2::Group>()?;
+            }
+            Ok(())
+        })?;
+    }
+
+    if defined {
+        Ok(())
+    } else {
+        Err(syn::Error::new(
+            Span::call_site(),
+            "`EndianStruct` requires `#[repr(C)]` or `#[repr(transparent)]`",
+        ))
+    }
+}
+
+fn endian_struct(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "`EndianStruct` can only be derived for structs",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "`EndianStruct` cannot be derived for generic structs",
+        ));
+    }
+    check_repr(input)?;
+
+    let Options { native, krate } = Options::parse(input)?;
+    let name = &input.ident;
+    let vis = &input.vis;
+    let trait_ = quote!(#krate::endian::EndianStruct);
+
+    let types = data
+        .fields
+        .iter()
+        .map(|field| &field.ty)
+        .collect::<Vec<_>>();
+    let members = data.fields.members().collect::<Vec<_>>();
+    let native_fields = data.fields.iter().map(|field| {
+        let vis = &field.vis;
+        let ident = field.ident.iter();
+        let ty = &field.ty;
+        quote!(#vis #(#ident:)* <#ty as #trait_>::Native)
+    });
+    let native_def = match &data.fields {
+        Fields::Named(_) => quote!(#vis struct #native { #(#native_fields,)* }),
+        Fields::Unnamed(_) => quote!(#vis struct #native(#(#native_fields,)*);),
+        Fields::Unit => quote!(#vis struct #native;),
+    };
+    let native_doc = format!("Native-endian counterpart of [`{name}`]");
+    let padding_msg = format!("`{name}` must not contain padding");
+
+    Ok(quote! {
+        #[doc = #native_doc]
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        #native_def
+
+        const _: () = ::core::assert!(
+            ::core::mem::size_of::<#name>() == 0 #(+ ::core::mem::size_of::<#types>())*,
+            #padding_msg,
+        );
+
+        // SAFETY: Every field implements `EndianStruct`, so has no invalid bit patterns or
+        // padding, and we've asserted there is no padding between the fields.
+        unsafe impl #trait_ for #name {
+            type Native = #native;
+
+            #[inline]
+            fn to_native(self) -> #native {
+                #native {
+                    #(#members: <#types as #trait_>::to_native(self.#members),)*
+                }
+            }
+
+            #[inline]
+            fn from_native(native: #native) -> Self {
+                Self {
+                    #(#members: <#types as #trait_>::from_native(native.#members),)*
+                }
+            }
+        }
+
+        impl #name {
+            /// Converts `self` to its native-endian counterpart
+            #[inline]
+            #vis fn to_native(self) -> #native {
+                <Self as #trait_>::to_native(self)
+            }
+
+            /// Converts a native-endian value to `Self`
+            #[inline]
+            #vis fn from_native(native: #native) -> Self {
+                <Self as #trait_>::from_native(native)
+            }
+
+            /// Reinterprets the start of `bytes` as a `Self`
+            ///
+            /// Returns `None` if `bytes` is too short or not suitably aligned.
+            #[inline]
+            #vis fn from_bytes(bytes: &[u8]) -> ::core::option::Option<&Self> {
+                <Self as #trait_>::from_bytes(bytes)
+            }
+        }
+    })
+}
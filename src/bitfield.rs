@@ -0,0 +1,700 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Bitfields and flag sets over integer storage
+//!
+//! The [`bitfield!`](crate::bitfield!) and [`flags!`](crate::flags!) macros declare types
+//! which wrap an integer, which may be one of the endian types (e.g. `u32_be`) so that the
+//! type can be used directly in a hardware descriptor or on-disk structure.
+
+/// An integer type which can back a bitfield
+pub trait Storage: Copy {
+    /// The native integer type
+    type Int: Copy;
+
+    /// The number of bits in the storage
+    const BITS: u32;
+
+    /// Returns the value as a native integer, zero-extended to 128 bits
+    fn to_bits(self) -> u128;
+
+    /// Creates a value from the low bits of `bits`
+    fn from_bits(bits: u128) -> Self;
+}
+
+/// A type which can be stored in a range of bits
+pub trait Field: Sized {
+    /// The type returned when reading the field
+    ///
+    /// This is `Self` for integers and `bool`, and `Option<Self>` for types which do not have
+    /// a value for every bit pattern, such as enums.
+    type Output;
+
+    /// Converts the `width` low bits of `bits` to a value
+    fn from_bits(bits: u128, width: u32) -> Self::Output;
+
+    /// Converts the value to bits
+    ///
+    /// Only the low bits which fit in the field are stored.
+    fn into_bits(self) -> u128;
+}
+
+/// Returns a mask of the bits `lo..hi`
+#[doc(hidden)]
+pub const fn mask(lo: u32, hi: u32) -> u128 {
+    (u128::MAX >> (128 - (hi - lo))) << lo
+}
+
+impl Field for bool {
+    type Output = bool;
+
+    #[inline(always)]
+    fn from_bits(bits: u128, _: u32) -> bool {
+        bits != 0
+    }
+
+    #[inline(always)]
+    fn into_bits(self) -> u128 {
+        self as u128
+    }
+}
+
+macro_rules! int_impls {
+    (unsigned: $($type:ty),*) => {$(
+        impl Storage for $type {
+            type Int = $type;
+
+            const BITS: u32 = <$type>::BITS;
+
+            #[inline(always)]
+            fn to_bits(self) -> u128 {
+                self as u128
+            }
+
+            #[inline(always)]
+            #[allow(clippy::cast_possible_truncation)]
+            fn from_bits(bits: u128) -> Self {
+                bits as $type
+            }
+        }
+
+        impl Field for $type {
+            type Output = $type;
+
+            #[inline(always)]
+            #[allow(clippy::cast_possible_truncation)]
+            fn from_bits(bits: u128, _: u32) -> $type {
+                bits as $type
+            }
+
+            #[inline(always)]
+            fn into_bits(self) -> u128 {
+                self as u128
+            }
+        }
+    )*};
+    (signed: $($type:ty),*) => {$(
+        impl Field for $type {
+            type Output = $type;
+
+            /// Sign-extends the field to the width of the type
+            #[inline(always)]
+            #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+            fn from_bits(bits: u128, width: u32) -> $type {
+                let shift = 128 - width;
+                (((bits << shift) as i128) >> shift) as $type
+            }
+
+            #[inline(always)]
+            #[allow(clippy::cast_sign_loss)]
+            fn into_bits(self) -> u128 {
+                self as u128
+            }
+        }
+    )*};
+}
+
+int_impls!(unsigned: u8, u16, u32, u64, u128, usize);
+int_impls!(signed: i8, i16, i32, i64, i128, isize);
+
+#[cfg(feature = "endian")]
+mod endian_impls {
+    use super::Storage;
+    use crate::endian::{ByteOrder, Endian, Unaligned};
+
+    macro_rules! storage_impls {
+        ($($type:ty),*) => {$(
+            impl<O: ByteOrder> Storage for Endian<$type, O> {
+                type Int = $type;
+
+                const BITS: u32 = <$type>::BITS;
+
+                #[inline(always)]
+                fn to_bits(self) -> u128 {
+                    self.get() as u128
+                }
+
+                #[inline(always)]
+                #[allow(clippy::cast_possible_truncation)]
+                fn from_bits(bits: u128) -> Self {
+                    Self::new(bits as $type)
+                }
+            }
+
+            impl<O: ByteOrder> Storage for Unaligned<$type, O> {
+                type Int = $type;
+
+                const BITS: u32 = <$type>::BITS;
+
+                #[inline(always)]
+                fn to_bits(self) -> u128 {
+                    self.get() as u128
+                }
+
+                #[inline(always)]
+                #[allow(clippy::cast_possible_truncation)]
+                fn from_bits(bits: u128) -> Self {
+                    Self::new(bits as $type)
+                }
+            }
+        )*};
+    }
+
+    storage_impls!(u8, u16, u32, u64, u128, usize);
+}
+
+/// Declares a type with named ranges of bits over an integer
+///
+/// Each field gives the name of its getter, optionally the name of its setter, its type, and
+/// its bits as either a single index or a half-open range. Fields may be of any type which
+/// implements [`Field`], including enums declared with [`bitfield_enum!`](crate::bitfield_enum!).
+///
+/// ```
+/// use libsa::endian::u16_le;
+///
+/// libsa::bitfield! {
+///     #[derive(Clone, Copy, Default)]
+///     pub struct PciCommand(u16_le) {
+///         pub io_space, set_io_space: bool [0];
+///         pub memory_space, set_memory_space: bool [1];
+///         pub bus_master, set_bus_master: bool [2];
+///         pub interrupt_disable, set_interrupt_disable: bool [10];
+///     }
+/// }
+///
+/// let mut command = PciCommand::default();
+/// command.set_bus_master(true);
+/// assert!(command.bus_master());
+/// assert_eq!(command.to_raw().get(), 1 << 2);
+/// ```
+#[macro_export]
+macro_rules! bitfield {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident($storage:ty) {
+            $(
+                $(#[$field_meta:meta])*
+                $field_vis:vis $get:ident $(, $set:ident)?: $field_ty:ty [$lo:literal $(.. $hi:literal)?];
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(transparent)]
+        $vis struct $name($storage);
+
+        impl $name {
+            /// Creates a value from its underlying storage
+            #[inline(always)]
+            pub const fn from_raw(raw: $storage) -> Self {
+                Self(raw)
+            }
+
+            /// Returns the underlying storage
+            #[inline(always)]
+            pub const fn to_raw(self) -> $storage {
+                self.0
+            }
+
+            $(
+                $(#[$field_meta])*
+                #[inline]
+                $field_vis fn $get(&self) -> <$field_ty as $crate::bitfield::Field>::Output {
+                    const LO: u32 = $lo;
+                    const HI: u32 = $crate::bitfield!(@hi $lo $(.. $hi)?);
+                    const _: () = ::core::assert!(
+                        LO < HI && HI <= <$storage as $crate::bitfield::Storage>::BITS,
+                        "bit range is out of bounds of the storage",
+                    );
+                    let bits = $crate::bitfield::Storage::to_bits(self.0);
+                    <$field_ty as $crate::bitfield::Field>::from_bits(
+                        (bits & $crate::bitfield::mask(LO, HI)) >> LO,
+                        HI - LO,
+                    )
+                }
+
+                $crate::bitfield! {
+                    @set [$($set)?] ($field_vis) $(#[$field_meta])*
+                    $field_ty [$lo $(.. $hi)?]
+                }
+            )*
+        }
+    };
+
+    (@set [] $($rest:tt)*) => {};
+    (@set [$set:ident] ($vis:vis) $(#[$meta:meta])* $ty:ty [$lo:literal $(.. $hi:literal)?]) => {
+        $(#[$meta])*
+        #[inline]
+        $vis fn $set(&mut self, value: $ty) {
+            const LO: u32 = $lo;
+            const HI: u32 = $crate::bitfield!(@hi $lo $(.. $hi)?);
+            let mask = $crate::bitfield::mask(LO, HI);
+            let bits = $crate::bitfield::Storage::to_bits(self.0);
+            let value = $crate::bitfield::Field::into_bits(value) << LO;
+            self.0 = $crate::bitfield::Storage::from_bits((bits & !mask) | (value & mask));
+        }
+    };
+
+    (@hi $lo:literal) => { $lo + 1 };
+    (@hi $lo:literal .. $hi:literal) => { $hi };
+}
+
+/// Declares a fieldless enum which can be used as a [`bitfield!`](crate::bitfield!) field
+///
+/// Reading the field returns `None` if the bits do not match any variant.
+///
+/// ```
+/// libsa::bitfield_enum! {
+///     #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+///     pub enum Trigger: u8 {
+///         Edge = 0,
+///         Level = 1,
+///     }
+/// }
+///
+/// libsa::bitfield! {
+///     pub struct Redirection(u32) {
+///         pub trigger, set_trigger: Trigger [15];
+///         pub vector, set_vector: u8 [0..8];
+///     }
+/// }
+///
+/// let entry = Redirection::from_raw(1 << 15 | 0x30);
+/// assert_eq!(entry.trigger(), Some(Trigger::Level));
+/// assert_eq!(entry.vector(), 0x30);
+/// ```
+#[macro_export]
+macro_rules! bitfield_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident: $repr:ty {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident = $value:expr
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr($repr)]
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant = $value,
+            )*
+        }
+
+        impl $crate::bitfield::Field for $name {
+            type Output = ::core::option::Option<Self>;
+
+            #[inline]
+            fn from_bits(bits: u128, _: u32) -> ::core::option::Option<Self> {
+                $(
+                    if bits == $name::$variant as u128 {
+                        return ::core::option::Option::Some($name::$variant);
+                    }
+                )*
+                ::core::option::Option::None
+            }
+
+            #[inline]
+            fn into_bits(self) -> u128 {
+                self as $repr as u128
+            }
+        }
+    };
+}
+
+/// Declares a set of flags over an endian integer
+///
+/// The storage must be one of the endian types, e.g. `u32_be` or `U64Le`. For native byte
+/// order use `Endian<T, NativeEndian>`.
+///
+/// ```
+/// use libsa::endian::u16_le;
+///
+/// libsa::flags! {
+///     #[derive(Clone, Copy, PartialEq, Eq)]
+///     pub struct DescFlags: u16_le {
+///         const NEXT = 1 << 0;
+///         const WRITE = 1 << 1;
+///         const INDIRECT = 1 << 2;
+///     }
+/// }
+///
+/// let flags = DescFlags::NEXT | DescFlags::WRITE;
+/// assert!(flags.contains(DescFlags::WRITE));
+/// assert_eq!(flags.to_raw().get(), 0b11);
+/// ```
+#[macro_export]
+macro_rules! flags {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident: $storage:ty {
+            $(
+                $(#[$flag_meta:meta])*
+                const $flag:ident = $value:expr;
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(transparent)]
+        $vis struct $name($storage);
+
+        #[allow(dead_code)]
+        impl $name {
+            $(
+                $(#[$flag_meta])*
+                pub const $flag: Self = Self::from_bits_retain($value);
+            )*
+
+            /// Returns a set with no flags
+            #[inline]
+            pub const fn empty() -> Self {
+                Self::from_bits_retain(0)
+            }
+
+            /// Returns a set with every defined flag
+            #[inline]
+            pub const fn all() -> Self {
+                Self::from_bits_retain(0 $(| Self::$flag.bits())*)
+            }
+
+            /// Returns the native value of the set
+            #[inline]
+            pub const fn bits(self) -> <$storage as $crate::bitfield::Storage>::Int {
+                self.0.get()
+            }
+
+            /// Creates a set from its native value, or `None` if any undefined bits are set
+            #[inline]
+            pub const fn from_bits(
+                bits: <$storage as $crate::bitfield::Storage>::Int,
+            ) -> ::core::option::Option<Self> {
+                if bits & !Self::all().bits() == 0 {
+                    ::core::option::Option::Some(Self::from_bits_retain(bits))
+                } else {
+                    ::core::option::Option::None
+                }
+            }
+
+            /// Creates a set from its native value, discarding any undefined bits
+            #[inline]
+            pub const fn from_bits_truncate(
+                bits: <$storage as $crate::bitfield::Storage>::Int,
+            ) -> Self {
+                Self::from_bits_retain(bits & Self::all().bits())
+            }
+
+            /// Creates a set from its native value, keeping any undefined bits
+            #[inline]
+            pub const fn from_bits_retain(
+                bits: <$storage as $crate::bitfield::Storage>::Int,
+            ) -> Self {
+                Self(<$storage>::new(bits))
+            }
+
+            /// Creates a set from its underlying storage
+            #[inline]
+            pub const fn from_raw(raw: $storage) -> Self {
+                Self(raw)
+            }
+
+            /// Returns the underlying storage
+            #[inline]
+            pub const fn to_raw(self) -> $storage {
+                self.0
+            }
+
+            #[inline]
+            pub const fn is_empty(self) -> bool {
+                self.bits() == 0
+            }
+
+            #[inline]
+            pub const fn is_all(self) -> bool {
+                self.bits() & Self::all().bits() == Self::all().bits()
+            }
+
+            /// Returns `true` if every flag in `other` is also in `self`
+            #[inline]
+            pub const fn contains(self, other: Self) -> bool {
+                self.bits() & other.bits() == other.bits()
+            }
+
+            /// Returns `true` if any flag in `other` is also in `self`
+            #[inline]
+            pub const fn intersects(self, other: Self) -> bool {
+                self.bits() & other.bits() != 0
+            }
+
+            #[inline]
+            pub const fn union(self, other: Self) -> Self {
+                Self::from_bits_retain(self.bits() | other.bits())
+            }
+
+            #[inline]
+            pub const fn intersection(self, other: Self) -> Self {
+                Self::from_bits_retain(self.bits() & other.bits())
+            }
+
+            #[inline]
+            pub const fn difference(self, other: Self) -> Self {
+                Self::from_bits_retain(self.bits() & !other.bits())
+            }
+
+            #[inline]
+            pub const fn symmetric_difference(self, other: Self) -> Self {
+                Self::from_bits_retain(self.bits() ^ other.bits())
+            }
+
+            /// Returns the defined flags which are not in `self`
+            #[inline]
+            pub const fn complement(self) -> Self {
+                Self::from_bits_truncate(!self.bits())
+            }
+
+            #[inline]
+            pub fn insert(&mut self, other: Self) {
+                *self = self.union(other);
+            }
+
+            #[inline]
+            pub fn remove(&mut self, other: Self) {
+                *self = self.difference(other);
+            }
+
+            #[inline]
+            pub fn toggle(&mut self, other: Self) {
+                *self = self.symmetric_difference(other);
+            }
+
+            /// Inserts or removes `other` depending on `value`
+            #[inline]
+            pub fn set(&mut self, other: Self, value: bool) {
+                if value {
+                    self.insert(other);
+                } else {
+                    self.remove(other);
+                }
+            }
+        }
+
+        impl ::core::ops::BitOr for $name {
+            type Output = Self;
+
+            #[inline]
+            fn bitor(self, rhs: Self) -> Self {
+                self.union(rhs)
+            }
+        }
+
+        impl ::core::ops::BitOrAssign for $name {
+            #[inline]
+            fn bitor_assign(&mut self, rhs: Self) {
+                self.insert(rhs);
+            }
+        }
+
+        impl ::core::ops::BitAnd for $name {
+            type Output = Self;
+
+            #[inline]
+            fn bitand(self, rhs: Self) -> Self {
+                self.intersection(rhs)
+            }
+        }
+
+        impl ::core::ops::BitAndAssign for $name {
+            #[inline]
+            fn bitand_assign(&mut self, rhs: Self) {
+                *self = self.intersection(rhs);
+            }
+        }
+
+        impl ::core::ops::BitXor for $name {
+            type Output = Self;
+
+            #[inline]
+            fn bitxor(self, rhs: Self) -> Self {
+                self.symmetric_difference(rhs)
+            }
+        }
+
+        impl ::core::ops::BitXorAssign for $name {
+            #[inline]
+            fn bitxor_assign(&mut self, rhs: Self) {
+                self.toggle(rhs);
+            }
+        }
+
+        impl ::core::ops::Sub for $name {
+            type Output = Self;
+
+            #[inline]
+            fn sub(self, rhs: Self) -> Self {
+                self.difference(rhs)
+            }
+        }
+
+        impl ::core::ops::SubAssign for $name {
+            #[inline]
+            fn sub_assign(&mut self, rhs: Self) {
+                self.remove(rhs);
+            }
+        }
+
+        impl ::core::ops::Not for $name {
+            type Output = Self;
+
+            #[inline]
+            fn not(self) -> Self {
+                self.complement()
+            }
+        }
+
+        impl ::core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                let mut remaining = self.bits();
+                let mut first = true;
+                f.write_str(::core::stringify!($name))?;
+                f.write_str("(")?;
+                $(
+                    if Self::$flag.bits() != 0 && self.contains(Self::$flag) {
+                        if !first {
+                            f.write_str(" | ")?;
+                        }
+                        first = false;
+                        f.write_str(::core::stringify!($flag))?;
+                        remaining &= !Self::$flag.bits();
+                    }
+                )*
+                if remaining != 0 {
+                    if !first {
+                        f.write_str(" | ")?;
+                    }
+                    first = false;
+                    ::core::write!(f, "{:#x}", remaining)?;
+                }
+                if first {
+                    f.write_str("empty")?;
+                }
+                f.write_str(")")
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::endian::u32_be;
+
+    crate::bitfield! {
+        #[derive(Clone, Copy)]
+        struct Fields(u32_be) {
+            low, set_low: u8 [0..4];
+            signed, set_signed: i8 [4..8];
+            flag, set_flag: bool [8];
+            wide, set_wide: i32 [9..32];
+        }
+    }
+
+    crate::flags! {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        struct Flags: u32_be {
+            const A = 1 << 0;
+            const B = 1 << 1;
+            const C = 1 << 4;
+        }
+    }
+
+    #[test]
+    fn sign_extension() {
+        let fields = Fields::from_raw(u32_be::new(0x8000_0080));
+        assert_eq!(fields.signed(), -8);
+        assert_eq!(fields.wide(), -(1 << 22));
+        assert_eq!(fields.low(), 0);
+
+        let fields = Fields::from_raw(u32_be::new(0x70));
+        assert_eq!(fields.signed(), 7);
+    }
+
+    #[test]
+    fn masking() {
+        let mut fields = Fields::from_raw(u32_be::new(0));
+        fields.set_low(0xff);
+        assert_eq!(fields.to_raw().get(), 0x0f);
+
+        fields.set_signed(-1);
+        assert_eq!(fields.to_raw().get(), 0xff);
+        fields.set_signed(0);
+        assert_eq!(fields.to_raw().get(), 0x0f);
+
+        fields.set_flag(true);
+        assert!(fields.flag());
+        fields.set_wide(-1);
+        assert_eq!(fields.to_raw().get(), 0xffff_ff0f);
+        assert_eq!(fields.wide(), -1);
+        assert_eq!(fields.low(), 0x0f);
+    }
+
+    #[test]
+    fn flags_debug() {
+        assert_eq!(format!("{:?}", Flags::empty()), "Flags(empty)");
+        assert_eq!(format!("{:?}", Flags::A | Flags::C), "Flags(A | C)");
+        assert_eq!(
+            format!("{:?}", Flags::from_bits_retain(0x102)),
+            "Flags(B | 0x100)"
+        );
+        assert_eq!(Flags::from_bits(0x100), None);
+        assert_eq!(
+            Flags::from_bits_truncate(0x113),
+            Flags::A | Flags::B | Flags::C
+        );
+    }
+}
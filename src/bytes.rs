@@ -0,0 +1,1043 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Cursors for reading and writing binary data in byte slices
+//!
+//! This module also provides the [`FromBytes`], [`AsBytes`] and [`Zeroable`] traits for
+//! zero-copy conversion between types and their in-memory bytes.
+//!
+//! With the `bytemuck` and `zerocopy` features enabled, the endian types also implement those
+//! crates' equivalent traits, and the [`bytes_from_bytemuck!`] and [`bytes_from_zerocopy!`]
+//! macros implement libsa's traits for types which already implement theirs. Blanket impls
+//! would overlap with the impls for primitives and arrays provided here.
+
+use crate::endian::{
+    BigEndian, ByteOrder, DynEndian, Endian, Endianness, LittleEndian, NativeEndian, NonZero,
+    NonZeroPrimitive, Primitive, Unaligned,
+};
+use core::{fmt, mem, ptr};
+
+/// An error returned when an access would go past the end of the buffer
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OutOfBounds {
+    /// The offset at which the access was attempted
+    pub offset: usize,
+    /// The number of bytes required by the access
+    pub needed: usize,
+    /// The number of bytes remaining in the buffer
+    pub available: usize,
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "access of {} bytes at offset {} is out of bounds ({} bytes available)",
+            self.needed, self.offset, self.available
+        )
+    }
+}
+
+pub type Result<T> = core::result::Result<T, OutOfBounds>;
+
+/// A type which can be decoded from a fixed number of bytes
+pub trait Decode: Sized {
+    /// The number of bytes needed to decode a value
+    const SIZE: usize;
+
+    /// Decodes a value from `bytes`
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if `bytes.len() != Self::SIZE`.
+    fn decode(bytes: &[u8]) -> Self;
+}
+
+/// A type which can be encoded in a fixed number of bytes
+pub trait Encode {
+    /// The number of bytes needed to encode a value
+    const SIZE: usize;
+
+    /// Encodes `self` into `bytes`
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if `bytes.len() != Self::SIZE`.
+    fn encode(&self, bytes: &mut [u8]);
+}
+
+/// Primitives are decoded in native byte order
+impl<T: Primitive> Decode for T {
+    const SIZE: usize = mem::size_of::<T>();
+
+    #[inline]
+    fn decode(bytes: &[u8]) -> Self {
+        Unaligned::<T, NativeEndian>::decode(bytes).get()
+    }
+}
+
+/// Primitives are encoded in native byte order
+impl<T: Primitive> Encode for T {
+    const SIZE: usize = mem::size_of::<T>();
+
+    #[inline]
+    fn encode(&self, bytes: &mut [u8]) {
+        Unaligned::<T, NativeEndian>::new(*self).encode(bytes);
+    }
+}
+
+impl<T: Primitive, O: ByteOrder> Decode for Unaligned<T, O> {
+    const SIZE: usize = mem::size_of::<T>();
+
+    #[inline]
+    fn decode(bytes: &[u8]) -> Self {
+        let mut array = T::Bytes::default();
+        array.as_mut().copy_from_slice(bytes);
+        Self::from_bytes(array)
+    }
+}
+
+impl<T: Primitive, O: ByteOrder> Encode for Unaligned<T, O> {
+    const SIZE: usize = mem::size_of::<T>();
+
+    #[inline]
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(self.to_bytes().as_ref());
+    }
+}
+
+impl<T: Primitive, O: ByteOrder> Decode for Endian<T, O> {
+    const SIZE: usize = mem::size_of::<T>();
+
+    #[inline]
+    fn decode(bytes: &[u8]) -> Self {
+        Unaligned::<T, O>::decode(bytes).into()
+    }
+}
+
+impl<T: Primitive, O: ByteOrder> Encode for Endian<T, O> {
+    const SIZE: usize = mem::size_of::<T>();
+
+    #[inline]
+    fn encode(&self, bytes: &mut [u8]) {
+        Unaligned::<T, O>::from(*self).encode(bytes);
+    }
+}
+
+impl<T: Decode, const N: usize> Decode for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+
+    #[inline]
+    fn decode(bytes: &[u8]) -> Self {
+        assert_eq!(bytes.len(), Self::SIZE);
+        core::array::from_fn(|i| T::decode(&bytes[i * T::SIZE..][..T::SIZE]))
+    }
+}
+
+impl<T: Encode, const N: usize> Encode for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+
+    #[inline]
+    fn encode(&self, bytes: &mut [u8]) {
+        assert_eq!(bytes.len(), Self::SIZE);
+        for (value, bytes) in self.iter().zip(bytes.chunks_exact_mut(T::SIZE)) {
+            value.encode(bytes);
+        }
+    }
+}
+
+/// A type for which the all-zero bit pattern is valid
+///
+/// # Safety
+///
+/// A value consisting entirely of zero bytes must be a valid `Self`.
+pub unsafe trait Zeroable: Sized {
+    #[inline]
+    fn zeroed() -> Self {
+        // SAFETY: The trait's safety requirements guarantee that zero is a valid `Self`.
+        unsafe { mem::zeroed() }
+    }
+}
+
+/// A type for which every bit pattern is valid
+///
+/// # Safety
+///
+/// Any sequence of `size_of::<Self>()` initialized bytes must be a valid `Self`.
+pub unsafe trait FromBytes: Zeroable {
+    /// Reinterprets the start of `bytes` as a `Self`
+    ///
+    /// Returns `None` if `bytes` is too short or not suitably aligned.
+    #[inline]
+    fn ref_from_prefix(bytes: &[u8]) -> Option<&Self> {
+        if bytes.len() < mem::size_of::<Self>() || !is_aligned::<Self>(bytes) {
+            return None;
+        }
+        // SAFETY: We've checked the size and alignment, and the trait's safety requirements
+        // guarantee that any bytes are a valid `Self`.
+        Some(unsafe { &*bytes.as_ptr().cast::<Self>() })
+    }
+
+    /// Reinterprets the start of `bytes` as a `Self`
+    ///
+    /// Returns `None` if `bytes` is too short or not suitably aligned.
+    #[inline]
+    fn mut_from_prefix(bytes: &mut [u8]) -> Option<&mut Self>
+    where
+        Self: AsBytes,
+    {
+        if bytes.len() < mem::size_of::<Self>() || !is_aligned::<Self>(bytes) {
+            return None;
+        }
+        // SAFETY: We've checked the size and alignment, and the safety requirements of
+        // `FromBytes` and `AsBytes` guarantee that any bytes are a valid `Self` and vice versa.
+        Some(unsafe { &mut *bytes.as_mut_ptr().cast::<Self>() })
+    }
+
+    /// Copies a `Self` out of the start of `bytes`, which need not be aligned
+    ///
+    /// Returns `None` if `bytes` is too short.
+    #[inline]
+    fn read_from_prefix(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < mem::size_of::<Self>() {
+            return None;
+        }
+        // SAFETY: We've checked the size, and the trait's safety requirements guarantee that
+        // any bytes are a valid `Self`.
+        Some(unsafe { bytes.as_ptr().cast::<Self>().read_unaligned() })
+    }
+
+    /// Reinterprets `bytes` as a slice of `Self`
+    ///
+    /// Returns `None` if `bytes` is not suitably aligned, its length is not a multiple of the
+    /// size of `Self`, or `Self` is zero-sized.
+    #[inline]
+    fn slice_from_bytes(bytes: &[u8]) -> Option<&[Self]> {
+        let len = slice_len::<Self>(bytes)?;
+        // SAFETY: We've checked the size and alignment, and the trait's safety requirements
+        // guarantee that any bytes are a valid `Self`.
+        Some(unsafe { core::slice::from_raw_parts(bytes.as_ptr().cast::<Self>(), len) })
+    }
+
+    /// Reinterprets `bytes` as a slice of `Self`
+    ///
+    /// Returns `None` if `bytes` is not suitably aligned, its length is not a multiple of the
+    /// size of `Self`, or `Self` is zero-sized.
+    #[inline]
+    fn slice_from_bytes_mut(bytes: &mut [u8]) -> Option<&mut [Self]>
+    where
+        Self: AsBytes,
+    {
+        let len = slice_len::<Self>(bytes)?;
+        // SAFETY: We've checked the size and alignment, and the safety requirements of
+        // `FromBytes` and `AsBytes` guarantee that any bytes are a valid `Self` and vice versa.
+        Some(unsafe { core::slice::from_raw_parts_mut(bytes.as_mut_ptr().cast::<Self>(), len) })
+    }
+}
+
+/// A type which can be viewed as its in-memory bytes
+///
+/// # Safety
+///
+/// `Self` must not contain any padding or interior mutability.
+pub unsafe trait AsBytes {
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        // SAFETY: The trait's safety requirements guarantee that `Self` has no padding, so
+        // every byte is initialized.
+        unsafe { core::slice::from_raw_parts(ptr::from_ref(self).cast(), mem::size_of_val(self)) }
+    }
+
+    #[inline]
+    fn as_bytes_mut(&mut self) -> &mut [u8]
+    where
+        Self: FromBytes,
+    {
+        // SAFETY: The safety requirements of `FromBytes` and `AsBytes` guarantee that any
+        // bytes are a valid `Self` and vice versa.
+        unsafe {
+            core::slice::from_raw_parts_mut(ptr::from_mut(self).cast(), mem::size_of_val(self))
+        }
+    }
+}
+
+#[inline]
+fn is_aligned<T>(bytes: &[u8]) -> bool {
+    bytes.as_ptr().align_offset(mem::align_of::<T>()) == 0
+}
+
+/// Returns the number of `T`s in `bytes`, if it can be reinterpreted as a `[T]`
+#[inline]
+fn slice_len<T>(bytes: &[u8]) -> Option<usize> {
+    let size = mem::size_of::<T>();
+    if size == 0 || !bytes.len().is_multiple_of(size) || !is_aligned::<T>(bytes) {
+        return None;
+    }
+    Some(bytes.len() / size)
+}
+
+macro_rules! plain_impls {
+    ($(impl$([$($params:tt)*])? for $type:ty;)*) => {$(
+        // SAFETY: Zero is a valid value of this type.
+        unsafe impl$(<$($params)*>)? Zeroable for $type {}
+        // SAFETY: Every bit pattern is a valid value of this type.
+        unsafe impl$(<$($params)*>)? FromBytes for $type {}
+        // SAFETY: This type does not contain padding or interior mutability.
+        unsafe impl$(<$($params)*>)? AsBytes for $type {}
+    )*};
+}
+
+plain_impls! {
+    impl[T: Primitive] for T;
+    impl[T: Primitive, O: ByteOrder] for Endian<T, O>;
+    impl[T: Primitive, O: ByteOrder] for Unaligned<T, O>;
+    impl[T: Primitive] for DynEndian<T>;
+    impl[T: FromBytes + AsBytes, const N: usize] for [T; N];
+    impl[] for ();
+}
+
+// SAFETY: `NonZero` is a transparent wrapper around a non-zero integer.
+unsafe impl<T: NonZeroPrimitive, O: ByteOrder> AsBytes for NonZero<T, O> {}
+
+plain_impls! {
+    impl[T: NonZeroPrimitive, O: ByteOrder] for Option<NonZero<T, O>>;
+}
+
+// SAFETY: A slice of `T` has no padding between its elements.
+unsafe impl<T: AsBytes> AsBytes for [T] {}
+
+/// Implements [`Zeroable`], [`FromBytes`] and [`AsBytes`] for types which implement
+/// [`bytemuck::Pod`]
+///
+/// ```
+/// # use libsa::bytes::FromBytes;
+/// # use libsa::endian::u32_le;
+/// #[derive(Clone, Copy, bytemuck::Zeroable, bytemuck::Pod)]
+/// #[repr(C)]
+/// struct Entry {
+///     base: u32_le,
+///     len: u32_le,
+/// }
+///
+/// libsa::bytes_from_bytemuck!(Entry);
+///
+/// let entry = Entry::read_from_prefix(&[1, 0, 0, 0, 2, 0, 0, 0]).unwrap();
+/// assert_eq!((entry.base.get(), entry.len.get()), (1, 2));
+/// ```
+#[cfg(feature = "bytemuck")]
+#[macro_export]
+macro_rules! bytes_from_bytemuck {
+    ($($type:ty),* $(,)?) => {$(
+        const _: () = $crate::bytes::assert_bytemuck::<$type>();
+        // SAFETY: `bytemuck::Pod` has the same requirements as these traits.
+        unsafe impl $crate::bytes::Zeroable for $type {}
+        // SAFETY: `bytemuck::Pod` has the same requirements as these traits.
+        unsafe impl $crate::bytes::FromBytes for $type {}
+        // SAFETY: `bytemuck::Pod` has the same requirements as these traits.
+        unsafe impl $crate::bytes::AsBytes for $type {}
+    )*};
+}
+
+/// Implements [`Zeroable`], [`FromBytes`] and [`AsBytes`] for types which implement
+/// [`zerocopy::FromBytes`], [`zerocopy::IntoBytes`] and [`zerocopy::Immutable`]
+///
+/// ```
+/// # use libsa::bytes::{AsBytes, FromBytes};
+/// # use libsa::endian::u32_be;
+/// #[derive(Clone, Copy, zerocopy::FromBytes, zerocopy::IntoBytes, zerocopy::Immutable)]
+/// #[repr(C)]
+/// struct Entry {
+///     base: u32_be,
+///     len: u32_be,
+/// }
+///
+/// libsa::bytes_from_zerocopy!(Entry);
+///
+/// let entry = Entry::read_from_prefix(&[0, 0, 0, 1, 0, 0, 0, 2]).unwrap();
+/// assert_eq!((entry.base.get(), entry.len.get()), (1, 2));
+/// assert_eq!(entry.as_bytes(), [0, 0, 0, 1, 0, 0, 0, 2]);
+/// ```
+#[cfg(feature = "zerocopy")]
+#[macro_export]
+macro_rules! bytes_from_zerocopy {
+    ($($type:ty),* $(,)?) => {$(
+        const _: () = $crate::bytes::assert_zerocopy::<$type>();
+        // SAFETY: `zerocopy::FromBytes` guarantees that any bytes are a valid `Self`.
+        unsafe impl $crate::bytes::Zeroable for $type {}
+        // SAFETY: `zerocopy::FromBytes` guarantees that any bytes are a valid `Self`.
+        unsafe impl $crate::bytes::FromBytes for $type {}
+        // SAFETY: `zerocopy::IntoBytes` and `zerocopy::Immutable` guarantee that `Self` has
+        // no padding or interior mutability.
+        unsafe impl $crate::bytes::AsBytes for $type {}
+    )*};
+}
+
+#[cfg(feature = "bytemuck")]
+#[doc(hidden)]
+pub const fn assert_bytemuck<T: bytemuck::Pod>() {}
+
+#[cfg(feature = "zerocopy")]
+#[doc(hidden)]
+pub const fn assert_zerocopy<T: zerocopy::FromBytes + zerocopy::IntoBytes + zerocopy::Immutable>() {
+}
+
+/// Defines `read_*` methods for each primitive in each byte order
+macro_rules! read_fns {
+    ($($le:ident, $be:ident: $type:ty;)*) => {$(
+        #[doc = concat!("Reads a little-endian `", stringify!($type), "`")]
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if too few bytes remain.
+        #[inline]
+        pub fn $le(&mut self) -> Result<$type> {
+            self.read_le()
+        }
+
+        #[doc = concat!("Reads a big-endian `", stringify!($type), "`")]
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if too few bytes remain.
+        #[inline]
+        pub fn $be(&mut self) -> Result<$type> {
+            self.read_be()
+        }
+    )*};
+}
+
+/// Defines `write_*` methods for each primitive in each byte order
+macro_rules! write_fns {
+    ($($le:ident, $be:ident: $type:ty;)*) => {$(
+        #[doc = concat!("Writes a little-endian `", stringify!($type), "`")]
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if too few bytes remain.
+        #[inline]
+        pub fn $le(&mut self, value: $type) -> Result<()> {
+            self.write_le(value)
+        }
+
+        #[doc = concat!("Writes a big-endian `", stringify!($type), "`")]
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if too few bytes remain.
+        #[inline]
+        pub fn $be(&mut self, value: $type) -> Result<()> {
+            self.write_be(value)
+        }
+    )*};
+}
+
+/// A cursor for reading binary data from a byte slice
+///
+/// Reads which would go past the end of the slice return an [`OutOfBounds`] error and do not
+/// advance the cursor.
+#[derive(Clone, Debug, Default)]
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    #[inline]
+    pub const fn new(bytes: &'a [u8]) -> ByteReader<'a> {
+        Self { bytes, offset: 0 }
+    }
+
+    /// Returns the current offset, relative to the start of the slice
+    #[inline]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the number of bytes left to read
+    #[inline]
+    pub const fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    /// Returns `true` if there are no bytes left to read
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Returns the bytes left to read, without advancing the cursor
+    #[inline]
+    pub fn remaining_bytes(&self) -> &'a [u8] {
+        &self.bytes[self.offset..]
+    }
+
+    /// Returns the entire underlying slice
+    #[inline]
+    pub const fn get_ref(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Moves the cursor to `offset`, relative to the start of the slice
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `offset` is past the end of the slice. The error describes an access
+    /// of `offset` bytes from the start of the slice.
+    #[inline]
+    pub fn seek(&mut self, offset: usize) -> Result<()> {
+        if offset > self.bytes.len() {
+            return Err(OutOfBounds {
+                offset: 0,
+                needed: offset,
+                available: self.bytes.len(),
+            });
+        }
+        self.offset = offset;
+        Ok(())
+    }
+
+    /// Returns the next `len` bytes, without advancing the cursor
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer than `len` bytes remain.
+    #[inline]
+    pub fn peek_bytes(&self, len: usize) -> Result<&'a [u8]> {
+        self.remaining_bytes().get(..len).ok_or(OutOfBounds {
+            offset: self.offset,
+            needed: len,
+            available: self.remaining(),
+        })
+    }
+
+    /// Reads the next `len` bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer than `len` bytes remain.
+    #[inline]
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self.peek_bytes(len)?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    /// Reads the next `N` bytes into an array
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer than `N` bytes remain.
+    #[inline]
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        self.read()
+    }
+
+    /// Skips the next `len` bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer than `len` bytes remain.
+    #[inline]
+    pub fn skip(&mut self, len: usize) -> Result<()> {
+        self.read_bytes(len).map(drop)
+    }
+
+    /// Skips bytes until the offset is a multiple of `align`
+    ///
+    /// The alignment is relative to the start of the slice, not the address of the data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the aligned offset is past the end of the slice.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `align` is zero.
+    #[inline]
+    pub fn align_to(&mut self, align: usize) -> Result<()> {
+        self.skip(self.offset.next_multiple_of(align) - self.offset)
+    }
+
+    /// Splits off a reader over the next `len` bytes, advancing this reader past them
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer than `len` bytes remain.
+    #[inline]
+    pub fn sub_reader(&mut self, len: usize) -> Result<ByteReader<'a>> {
+        self.read_bytes(len).map(ByteReader::new)
+    }
+
+    /// Decodes a `T` without advancing the cursor
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer than `T::SIZE` bytes remain.
+    #[inline]
+    pub fn peek<T: Decode>(&self) -> Result<T> {
+        self.peek_bytes(T::SIZE).map(T::decode)
+    }
+
+    /// Decodes a `T`
+    ///
+    /// Endian types are decoded in their own byte order, and primitives in native byte order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer than `T::SIZE` bytes remain.
+    #[inline]
+    pub fn read<T: Decode>(&mut self) -> Result<T> {
+        self.read_bytes(T::SIZE).map(T::decode)
+    }
+
+    /// Reads a `T` stored in `O` byte order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if too few bytes remain.
+    #[inline]
+    pub fn read_order<T: Primitive, O: ByteOrder>(&mut self) -> Result<T> {
+        self.read::<Unaligned<T, O>>().map(Unaligned::get)
+    }
+
+    /// Reads a little-endian `T`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if too few bytes remain.
+    #[inline]
+    pub fn read_le<T: Primitive>(&mut self) -> Result<T> {
+        self.read_order::<T, LittleEndian>()
+    }
+
+    /// Reads a big-endian `T`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if too few bytes remain.
+    #[inline]
+    pub fn read_be<T: Primitive>(&mut self) -> Result<T> {
+        self.read_order::<T, BigEndian>()
+    }
+
+    /// Reads a `T` stored in `endianness` byte order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if too few bytes remain.
+    #[inline]
+    pub fn read_endian<T: Primitive>(&mut self, endianness: Endianness) -> Result<T> {
+        match endianness {
+            Endianness::Little => self.read_le(),
+            Endianness::Big => self.read_be(),
+        }
+    }
+
+    /// Reads a byte
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no bytes remain.
+    #[inline]
+    pub fn read_u8(&mut self) -> Result<u8> {
+        self.read()
+    }
+
+    /// Reads a signed byte
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no bytes remain.
+    #[inline]
+    pub fn read_i8(&mut self) -> Result<i8> {
+        self.read()
+    }
+
+    read_fns! {
+        read_u16_le, read_u16_be: u16;
+        read_u32_le, read_u32_be: u32;
+        read_u64_le, read_u64_be: u64;
+        read_u128_le, read_u128_be: u128;
+        read_i16_le, read_i16_be: i16;
+        read_i32_le, read_i32_be: i32;
+        read_i64_le, read_i64_be: i64;
+        read_i128_le, read_i128_be: i128;
+        read_f32_le, read_f32_be: f32;
+        read_f64_le, read_f64_be: f64;
+    }
+}
+
+/// A cursor for writing binary data into a byte slice
+///
+/// Writes which would go past the end of the slice return an [`OutOfBounds`] error and do
+/// not advance the cursor.
+#[derive(Debug, Default)]
+pub struct ByteWriter<'a> {
+    bytes: &'a mut [u8],
+    offset: usize,
+}
+
+impl<'a> ByteWriter<'a> {
+    #[inline]
+    pub fn new(bytes: &'a mut [u8]) -> ByteWriter<'a> {
+        Self { bytes, offset: 0 }
+    }
+
+    /// Returns the current offset, relative to the start of the slice
+    #[inline]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the number of bytes which can still be written
+    #[inline]
+    pub const fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    /// Returns `true` if no more bytes can be written
+    #[inline]
+    pub const fn is_full(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Returns the bytes written so far
+    #[inline]
+    pub fn written(&self) -> &[u8] {
+        &self.bytes[..self.offset]
+    }
+
+    /// Consumes the writer, returning the bytes written
+    #[inline]
+    pub fn into_written(self) -> &'a mut [u8] {
+        &mut self.bytes[..self.offset]
+    }
+
+    /// Moves the cursor to `offset`, relative to the start of the slice
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `offset` is past the end of the slice. The error describes an access
+    /// of `offset` bytes from the start of the slice.
+    #[inline]
+    pub fn seek(&mut self, offset: usize) -> Result<()> {
+        if offset > self.bytes.len() {
+            return Err(OutOfBounds {
+                offset: 0,
+                needed: offset,
+                available: self.bytes.len(),
+            });
+        }
+        self.offset = offset;
+        Ok(())
+    }
+
+    /// Returns the next `len` bytes for writing, advancing the cursor past them
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer than `len` bytes remain.
+    #[inline]
+    pub fn reserve(&mut self, len: usize) -> Result<&mut [u8]> {
+        let available = self.remaining();
+        let offset = self.offset;
+        let bytes = self.bytes[offset..].get_mut(..len).ok_or(OutOfBounds {
+            offset,
+            needed: len,
+            available,
+        })?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    /// Writes `bytes`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer than `bytes.len()` bytes remain.
+    #[inline]
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        self.reserve(bytes.len())?.copy_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Writes `len` zero bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer than `len` bytes remain.
+    #[inline]
+    pub fn skip(&mut self, len: usize) -> Result<()> {
+        self.reserve(len)?.fill(0);
+        Ok(())
+    }
+
+    /// Writes zero bytes until the offset is a multiple of `align`
+    ///
+    /// The alignment is relative to the start of the slice, not the address of the data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the aligned offset is past the end of the slice.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `align` is zero.
+    #[inline]
+    pub fn align_to(&mut self, align: usize) -> Result<()> {
+        self.skip(self.offset.next_multiple_of(align) - self.offset)
+    }
+
+    /// Encodes `value`
+    ///
+    /// Endian types are encoded in their own byte order, and primitives in native byte order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer than `T::SIZE` bytes remain.
+    #[inline]
+    pub fn write<T: Encode>(&mut self, value: &T) -> Result<()> {
+        value.encode(self.reserve(T::SIZE)?);
+        Ok(())
+    }
+
+    /// Writes `value` in `O` byte order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if too few bytes remain.
+    #[inline]
+    pub fn write_order<T: Primitive, O: ByteOrder>(&mut self, value: T) -> Result<()> {
+        self.write(&Unaligned::<T, O>::new(value))
+    }
+
+    /// Writes `value` in little-endian byte order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if too few bytes remain.
+    #[inline]
+    pub fn write_le<T: Primitive>(&mut self, value: T) -> Result<()> {
+        self.write_order::<T, LittleEndian>(value)
+    }
+
+    /// Writes `value` in big-endian byte order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if too few bytes remain.
+    #[inline]
+    pub fn write_be<T: Primitive>(&mut self, value: T) -> Result<()> {
+        self.write_order::<T, BigEndian>(value)
+    }
+
+    /// Writes `value` in `endianness` byte order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if too few bytes remain.
+    #[inline]
+    pub fn write_endian<T: Primitive>(&mut self, value: T, endianness: Endianness) -> Result<()> {
+        match endianness {
+            Endianness::Little => self.write_le(value),
+            Endianness::Big => self.write_be(value),
+        }
+    }
+
+    /// Writes a byte
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no bytes remain.
+    #[inline]
+    pub fn write_u8(&mut self, value: u8) -> Result<()> {
+        self.write(&value)
+    }
+
+    /// Writes a signed byte
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no bytes remain.
+    #[inline]
+    pub fn write_i8(&mut self, value: i8) -> Result<()> {
+        self.write(&value)
+    }
+
+    write_fns! {
+        write_u16_le, write_u16_be: u16;
+        write_u32_le, write_u32_be: u32;
+        write_u64_le, write_u64_be: u64;
+        write_u128_le, write_u128_be: u128;
+        write_i16_le, write_i16_be: i16;
+        write_i32_le, write_i32_be: i32;
+        write_i64_le, write_i64_be: i64;
+        write_i128_le, write_i128_be: i128;
+        write_f32_le, write_f32_be: f32;
+        write_f64_le, write_f64_be: f64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncated_read() {
+        let mut reader = ByteReader::new(&[1, 2, 3, 4, 5]);
+        assert_eq!(reader.read_u16_be(), Ok(0x0102));
+        assert_eq!(
+            reader.read_u32_le(),
+            Err(OutOfBounds {
+                offset: 2,
+                needed: 4,
+                available: 3,
+            })
+        );
+        assert_eq!(reader.offset(), 2);
+        assert_eq!(reader.read_array::<3>(), Ok([3, 4, 5]));
+        assert!(reader.is_empty());
+        assert!(reader.read_u8().is_err());
+    }
+
+    #[test]
+    fn reader_align_to() {
+        let mut reader = ByteReader::new(&[0; 10]);
+        reader.skip(1).unwrap();
+        reader.align_to(4).unwrap();
+        assert_eq!(reader.offset(), 4);
+        reader.align_to(4).unwrap();
+        assert_eq!(reader.offset(), 4);
+        reader.skip(5).unwrap();
+        assert!(reader.align_to(4).is_err());
+        assert_eq!(reader.offset(), 9);
+    }
+
+    #[test]
+    fn sub_reader() {
+        let mut reader = ByteReader::new(&[1, 2, 3, 4, 5]);
+        reader.skip(1).unwrap();
+        let mut sub = reader.sub_reader(3).unwrap();
+        assert_eq!(reader.offset(), 4);
+        assert_eq!(sub.read_u16_le(), Ok(0x0302));
+        assert_eq!(sub.offset(), 2);
+        assert_eq!(sub.read_u16_le().unwrap_err().available, 1);
+        assert!(reader.sub_reader(2).is_err());
+        assert_eq!(reader.read_u8(), Ok(5));
+    }
+
+    #[test]
+    fn reader_seek() {
+        let mut reader = ByteReader::new(&[1, 2, 3]);
+        reader.seek(2).unwrap();
+        assert_eq!(reader.read_u8(), Ok(3));
+        reader.seek(3).unwrap();
+        assert!(reader.is_empty());
+        assert_eq!(
+            reader.seek(7),
+            Err(OutOfBounds {
+                offset: 0,
+                needed: 7,
+                available: 3,
+            })
+        );
+        assert_eq!(reader.offset(), 3);
+    }
+
+    #[test]
+    fn writer_overflow() {
+        let mut buf = [0; 6];
+        let mut writer = ByteWriter::new(&mut buf);
+        writer.write_u32_be(0x0102_0304).unwrap();
+        assert_eq!(
+            writer.write_u32_le(0),
+            Err(OutOfBounds {
+                offset: 4,
+                needed: 4,
+                available: 2,
+            })
+        );
+        assert_eq!(writer.offset(), 4);
+        writer.write_u16_le(0x0605).unwrap();
+        assert!(writer.is_full());
+        assert!(writer.write_u8(0).is_err());
+        assert_eq!(buf, [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn writer_seek_and_align() {
+        let mut buf = [0xff; 8];
+        let mut writer = ByteWriter::new(&mut buf);
+        writer.write_u8(1).unwrap();
+        writer.align_to(4).unwrap();
+        assert_eq!(writer.offset(), 4);
+        assert_eq!(writer.seek(9).unwrap_err().needed, 9);
+        writer.seek(6).unwrap();
+        writer.write_u16_be(0x0708).unwrap();
+        assert!(writer.align_to(16).is_err());
+        assert_eq!(buf, [1, 0, 0, 0, 0xff, 0xff, 7, 8]);
+    }
+
+    #[test]
+    fn optional_nonzero() {
+        use crate::endian::NonZeroU32Be;
+
+        let bytes = [0, 0, 0, 0, 0, 0, 1, 0];
+        let values = <Option<NonZeroU32Be>>::slice_from_bytes(&bytes[..4]).unwrap();
+        assert_eq!(values, [None]);
+        let value = <Option<NonZeroU32Be>>::read_from_prefix(&bytes[4..]).unwrap();
+        assert_eq!(value, NonZeroU32Be::new(0x100));
+        assert_eq!(value.as_bytes(), &bytes[4..]);
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn bytemuck_nonzero() {
+        use crate::endian::NonZeroU32Be;
+
+        let value: Option<NonZeroU32Be> = bytemuck::pod_read_unaligned(&[0, 0, 1, 2]);
+        assert_eq!(value, NonZeroU32Be::new(0x0102));
+        let value: NonZeroU32Be = bytemuck::checked::pod_read_unaligned(&[0, 0, 1, 2]);
+        assert_eq!(bytemuck::bytes_of(&value), [0, 0, 1, 2]);
+        assert!(bytemuck::checked::try_pod_read_unaligned::<NonZeroU32Be>(&[0; 4]).is_err());
+    }
+
+    #[cfg(feature = "zerocopy")]
+    #[test]
+    fn zerocopy_nonzero() {
+        use crate::endian::NonZeroU32Be;
+        use zerocopy::{IntoBytes, TryFromBytes};
+
+        let value = NonZeroU32Be::try_read_from_bytes(&[0, 0, 1, 2]).unwrap();
+        assert_eq!(value.get().get(), 0x0102);
+        assert_eq!(IntoBytes::as_bytes(&value), [0, 0, 1, 2]);
+        assert!(NonZeroU32Be::try_read_from_bytes(&[0; 4]).is_err());
+    }
+}
@@ -28,537 +28,1214 @@
  * SPDX-License-Identifier: BSD-3-Clause
  */
 
-use core::{cmp, fmt, ops};
+//! Integer and floating-point types stored in a fixed byte order
+//!
+//! [`Endian<T, O>`] stores a primitive `T` in the byte order described by `O`, converting
+//! to and from the native order on access. The familiar names (e.g. [`LittleEndianU32`] and
+//! [`u32_be`]) are aliases of `Endian` for a particular primitive and byte order, which allows
+//! code to be written generically over the byte order.
+//!
+//! [`Unaligned<T, O>`] is the same, but with an alignment of 1 for use in packed structures.
+//! Its aliases are named after the primitive and byte order, e.g. [`U32Le`] and [`I64Be`].
+//!
+//! [`Atomic<T, O>`] provides atomic access to a `T` stored in `O` byte order, for counters
+//! in memory shared with devices or other processors, e.g. [`AtomicU32Le`].
+//!
+//! [`NonZero<T, O>`] is a non-zero `T` stored in `O` byte order, such that
+//! `Option<NonZeroU32Le>` is the same size as a `u32`.
+//!
+//! Formats which declare their byte order at runtime can use [`Endianness`] with
+//! [`DynEndian`] or the [`read()`] and [`write()`] functions instead.
+//!
+//! Whole slices can be converted at once with [`convert_slice_to_native()`],
+//! [`swap_in_place()`] and [`read_iter()`], which avoid converting each element by hand.
+
+use core::{cmp, fmt, hash, marker::PhantomData, mem, ops, ptr};
+
+mod atomic;
+mod nonzero;
+#[cfg(feature = "serde")]
+mod serde;
+mod slice;
+
+pub use self::{atomic::*, nonzero::*, slice::*};
+
+mod private {
+    pub trait Sealed {}
+}
 
-macro_rules! fmt_impls {
-    ($name:ident: $($fmt:ident),*) => {$(
-        impl fmt::$fmt for $name {
-            #[inline(always)]
-            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                fmt::$fmt::fmt(&self.get(), f)
-            }
-        }
-    )*};
+/// A byte order known only at runtime
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Endianness {
+    Little,
+    Big,
 }
 
-macro_rules! op_assign_impl {
-    ($lhs:ty, $rhs:ty) => {
-        impl ops::AddAssign<$rhs> for $lhs {
-            #[inline(always)]
-            fn add_assign(&mut self, rhs: $rhs) {
-                *self = *self + rhs;
-            }
+impl Endianness {
+    /// The byte order of the target
+    #[cfg(target_endian = "little")]
+    pub const NATIVE: Self = Self::Little;
+    /// The byte order of the target
+    #[cfg(target_endian = "big")]
+    pub const NATIVE: Self = Self::Big;
+
+    #[inline(always)]
+    pub const fn is_native(self) -> bool {
+        matches!(
+            (self, Self::NATIVE),
+            (Self::Little, Self::Little) | (Self::Big, Self::Big)
+        )
+    }
+
+    /// Converts `value` between the native byte order and `self`
+    ///
+    /// The conversion is its own inverse, so this is used for both directions.
+    #[inline(always)]
+    pub const fn convert<T: Primitive>(self, value: T) -> T {
+        // SAFETY: `T::Bits` is the same size as `T`, and `Primitive` is only implemented for
+        // types without invalid bit patterns.
+        unsafe { transmute(self.encode(value)) }
+    }
+
+    /// Converts `value` to its bits, stored in `self` byte order
+    #[inline(always)]
+    const fn encode<T: Primitive>(self, value: T) -> T::Bits {
+        // SAFETY: `T::Bits` is the same size as `T` and has no invalid bit patterns.
+        let bits = unsafe { transmute::<T, T::Bits>(value) };
+        if self.is_native() {
+            bits
+        } else {
+            swap_bytes(bits)
         }
+    }
+
+    /// Converts bits stored in `self` byte order to a value
+    #[inline(always)]
+    const fn decode<T: Primitive>(self, bits: T::Bits) -> T {
+        let bits = if self.is_native() {
+            bits
+        } else {
+            swap_bytes(bits)
+        };
+        // SAFETY: `T::Bits` is the same size as `T`, and `Primitive` is only implemented for
+        // types without invalid bit patterns.
+        unsafe { transmute(bits) }
+    }
+}
 
-        impl ops::BitAndAssign<$rhs> for $lhs {
-            #[inline(always)]
-            fn bitand_assign(&mut self, rhs: $rhs) {
-                *self = *self & rhs;
-            }
-        }
+/// A byte order
+///
+/// This trait is sealed and implemented only by [`LittleEndian`] and [`BigEndian`].
+pub trait ByteOrder:
+    Copy + Default + fmt::Debug + Eq + hash::Hash + Ord + private::Sealed + 'static
+{
+    const ENDIANNESS: Endianness;
+}
 
-        impl ops::BitOrAssign<$rhs> for $lhs {
-            #[inline(always)]
-            fn bitor_assign(&mut self, rhs: $rhs) {
-                *self = *self | rhs;
-            }
-        }
+/// Least-significant byte first
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct LittleEndian;
 
-        impl ops::BitXorAssign<$rhs> for $lhs {
-            #[inline(always)]
-            fn bitxor_assign(&mut self, rhs: $rhs) {
-                *self = *self ^ rhs;
-            }
-        }
+impl private::Sealed for LittleEndian {}
 
-        impl ops::DivAssign<$rhs> for $lhs {
-            #[inline(always)]
-            fn div_assign(&mut self, rhs: $rhs) {
-                *self = *self / rhs;
-            }
-        }
+impl ByteOrder for LittleEndian {
+    const ENDIANNESS: Endianness = Endianness::Little;
+}
 
-        impl ops::MulAssign<$rhs> for $lhs {
-            #[inline(always)]
-            fn mul_assign(&mut self, rhs: $rhs) {
-                *self = *self * rhs;
-            }
-        }
+/// Most-significant byte first
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct BigEndian;
 
-        impl ops::RemAssign<$rhs> for $lhs {
-            #[inline(always)]
-            fn rem_assign(&mut self, rhs: $rhs) {
-                *self = *self % rhs;
-            }
-        }
+impl private::Sealed for BigEndian {}
 
-        impl ops::ShlAssign<$rhs> for $lhs {
-            #[inline(always)]
-            fn shl_assign(&mut self, rhs: $rhs) {
-                *self = *self << rhs;
-            }
-        }
+impl ByteOrder for BigEndian {
+    const ENDIANNESS: Endianness = Endianness::Big;
+}
 
-        impl ops::ShrAssign<$rhs> for $lhs {
-            #[inline(always)]
-            fn shr_assign(&mut self, rhs: $rhs) {
-                *self = *self >> rhs;
-            }
-        }
+/// The byte order of the target
+#[cfg(target_endian = "little")]
+pub type NativeEndian = LittleEndian;
+
+/// The byte order of the target
+#[cfg(target_endian = "big")]
+pub type NativeEndian = BigEndian;
+
+/// A primitive type which can be stored by [`Endian`]
+///
+/// This trait is sealed and implemented only for the primitive integer and floating-point
+/// types.
+pub trait Primitive: Copy + private::Sealed + 'static {
+    /// The unsigned integer type with the same size as `Self`
+    ///
+    /// Values are always stored as bits, so that byte-swapped floating-point values are never
+    /// loaded into floating-point registers, which could alter them (e.g. quieting a
+    /// signaling NaN).
+    type Bits: Primitive<Bits = Self::Bits>
+        + Eq
+        + hash::Hash
+        + Default
+        + ops::BitAnd<Output = Self::Bits>
+        + ops::BitOr<Output = Self::Bits>
+        + ops::BitXor<Output = Self::Bits>
+        + ops::Not<Output = Self::Bits>;
+    /// A byte array of the same size as `Self`
+    type Bytes: Copy + Default + Eq + hash::Hash + AsRef<[u8]> + AsMut<[u8]> + 'static;
+}
 
-        impl ops::SubAssign<$rhs> for $lhs {
-            #[inline(always)]
-            fn sub_assign(&mut self, rhs: $rhs) {
-                *self = *self - rhs;
-            }
-        }
-    };
+/// Reinterprets the bits of `value` as a `U`
+///
+/// # Safety
+///
+/// The bits of `value` must be a valid `U`.
+#[inline(always)]
+const unsafe fn transmute<T: Copy, U: Copy>(value: T) -> U {
+    assert!(mem::size_of::<T>() == mem::size_of::<U>());
+    // SAFETY: The caller guarantees that `value` is a valid `U`, and we've just checked the
+    // sizes match.
+    unsafe { ptr::addr_of!(value).cast::<U>().read_unaligned() }
 }
 
-macro_rules! endian_ints {
-    (
-        signed;
-        from: $from:ident;
-        to:   $to:ident;
+/// Reverses the byte order of `value`
+///
+/// This is [`u32::swap_bytes()`] and friends, but callable from a generic `const fn`.
+#[inline(always)]
+const fn swap_bytes<T: Primitive>(value: T) -> T {
+    macro_rules! swap {
+        ($int:ty) => {{
+            // SAFETY: `Primitive` is only implemented for types without invalid bit patterns,
+            // and we've just checked that `T` and `$int` are the same size.
+            let swapped = unsafe { transmute::<T, $int>(value) }.swap_bytes();
+            // SAFETY: See above.
+            unsafe { transmute::<$int, T>(swapped) }
+        }};
+    }
+
+    match mem::size_of::<T>() {
+        1 => value,
+        2 => swap!(u16),
+        4 => swap!(u32),
+        8 => swap!(u64),
+        16 => swap!(u128),
+        _ => unreachable!(),
+    }
+}
 
-        $(type $name:ident = $short:ident = $type:ty;)*
-    ) => {
-        endian_ints! {
-            from: $from;
-            to:   $to;
+/// A value of type `T` stored in `O` byte order
+#[repr(transparent)]
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(
+        zerocopy::FromBytes,
+        zerocopy::Immutable,
+        zerocopy::IntoBytes,
+        zerocopy::KnownLayout
+    )
+)]
+pub struct Endian<T: Primitive, O> {
+    bits: T::Bits,
+    order: PhantomData<O>,
+}
 
-            $(type $name = $short = $type;)*
+impl<T: Primitive, O: ByteOrder> Endian<T, O> {
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        Self::from_raw(O::ENDIANNESS.encode(value))
+    }
+
+    #[inline(always)]
+    pub const fn get(self) -> T {
+        O::ENDIANNESS.decode(self.bits)
+    }
+
+    /// Creates a value from its representation in memory, without any conversion
+    #[inline(always)]
+    pub const fn from_raw(raw: T::Bits) -> Self {
+        Self {
+            bits: raw,
+            order: PhantomData,
         }
+    }
 
-        $(
-            impl ops::Neg for $name {
-                type Output = Self;
+    /// Returns the representation of the value in memory, without any conversion
+    #[inline(always)]
+    pub const fn to_raw(self) -> T::Bits {
+        self.bits
+    }
+}
 
-                fn neg(self) -> Self {
-                    Self::new(-self.get())
-                }
-            }
-        )*
-    };
+#[cfg(feature = "bytemuck")]
+// SAFETY: `Endian` is a transparent wrapper around an integer.
+unsafe impl<T: Primitive, O: ByteOrder> bytemuck::Zeroable for Endian<T, O> {}
+
+#[cfg(feature = "bytemuck")]
+// SAFETY: `Endian` is a transparent wrapper around an integer.
+unsafe impl<T: Primitive, O: ByteOrder> bytemuck::Pod for Endian<T, O> {}
+
+/// A value of type `T` stored in `O` byte order, with an alignment of 1
+///
+/// This is the same as [`Endian`], but can be used in `#[repr(packed)]` structures and other
+/// places where the alignment of `T` cannot be guaranteed.
+#[repr(transparent)]
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(
+        zerocopy::FromBytes,
+        zerocopy::Immutable,
+        zerocopy::IntoBytes,
+        zerocopy::KnownLayout,
+        zerocopy::Unaligned
+    )
+)]
+pub struct Unaligned<T: Primitive, O> {
+    bytes: T::Bytes,
+    order: PhantomData<O>,
+}
 
-    (
-        from: $from:ident;
-        to:   $to:ident;
+impl<T: Primitive, O: ByteOrder> Unaligned<T, O> {
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        // SAFETY: `T::Bytes` is the same size as `T::Bits`.
+        Self::from_bytes(unsafe { transmute(O::ENDIANNESS.encode(value)) })
+    }
+
+    #[inline(always)]
+    pub const fn get(self) -> T {
+        // SAFETY: `T::Bits` is the same size as `T::Bytes` and has no invalid bit patterns.
+        O::ENDIANNESS.decode(unsafe { transmute(self.bytes) })
+    }
+
+    /// Creates a value from its representation in memory
+    #[inline(always)]
+    pub const fn from_bytes(bytes: T::Bytes) -> Self {
+        Self {
+            bytes,
+            order: PhantomData,
+        }
+    }
 
-        $(type $name:ident = $short:ident = $type:ty;)*
-    ) => {$(
+    /// Returns the representation of the value in memory
+    #[inline(always)]
+    pub const fn to_bytes(self) -> T::Bytes {
+        self.bytes
+    }
+}
 
-        #[repr(transparent)]
-        #[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
-        #[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
-        pub struct $name($type);
+impl<T: Primitive, O: ByteOrder> From<Endian<T, O>> for Unaligned<T, O> {
+    #[inline(always)]
+    fn from(val: Endian<T, O>) -> Self {
+        // SAFETY: `T::Bytes` is the same size as `T::Bits`.
+        Self::from_bytes(unsafe { transmute(val.to_raw()) })
+    }
+}
 
-        #[allow(non_camel_case_types)]
-        pub type $short = $name;
+impl<T: Primitive, O: ByteOrder> From<Unaligned<T, O>> for Endian<T, O> {
+    #[inline(always)]
+    fn from(val: Unaligned<T, O>) -> Self {
+        // SAFETY: `T::Bits` is the same size as `T::Bytes` and has no invalid bit patterns.
+        Self::from_raw(unsafe { transmute(val.to_bytes()) })
+    }
+}
 
-        impl $name {
-            pub const MIN: Self = Self::new(<$type>::MIN);
-            pub const MAX: Self = Self::new(<$type>::MAX);
-            pub const BITS: u32 = <$type>::BITS;
+#[cfg(feature = "bytemuck")]
+// SAFETY: `Unaligned` is a transparent wrapper around a byte array.
+unsafe impl<T: Primitive, O: ByteOrder> bytemuck::Zeroable for Unaligned<T, O> {}
+
+#[cfg(feature = "bytemuck")]
+// SAFETY: `Unaligned` is a transparent wrapper around a byte array.
+unsafe impl<T: Primitive, O: ByteOrder> bytemuck::Pod for Unaligned<T, O> {}
+
+/// A value of type `T` stored in a byte order known only at runtime
+///
+/// Unlike [`Endian`], the byte order is not part of the type, so it must be supplied on each
+/// access. This is intended for formats which declare their byte order in a header, such as
+/// ELF and FDT.
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(
+        zerocopy::FromBytes,
+        zerocopy::Immutable,
+        zerocopy::IntoBytes,
+        zerocopy::KnownLayout
+    )
+)]
+pub struct DynEndian<T: Primitive>(T::Bits);
+
+impl<T: Primitive> DynEndian<T> {
+    #[inline(always)]
+    pub const fn new(value: T, endianness: Endianness) -> Self {
+        Self(endianness.encode(value))
+    }
+
+    #[inline(always)]
+    pub const fn get(self, endianness: Endianness) -> T {
+        endianness.decode(self.0)
+    }
+
+    /// Sets the value, stored in `endianness` byte order
+    #[inline(always)]
+    pub fn set(&mut self, value: T, endianness: Endianness) {
+        *self = Self::new(value, endianness);
+    }
+
+    /// Creates a value from its representation in memory, without any conversion
+    #[inline(always)]
+    pub const fn from_raw(raw: T::Bits) -> Self {
+        Self(raw)
+    }
+
+    /// Returns the representation of the value in memory, without any conversion
+    #[inline(always)]
+    pub const fn to_raw(self) -> T::Bits {
+        self.0
+    }
+}
 
-            pub const fn new(value: $type) -> Self {
-                Self(value.$to())
-            }
+impl<T: Primitive, O: ByteOrder> From<Endian<T, O>> for DynEndian<T> {
+    #[inline(always)]
+    fn from(val: Endian<T, O>) -> Self {
+        Self(val.to_raw())
+    }
+}
 
-            #[inline(always)]
-            pub const fn get(self) -> $type {
-                <$type>::$from(self.0)
-            }
-        }
+impl<T: Primitive> fmt::Debug for DynEndian<T>
+where
+    T::Bits: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DynEndian").field(&self.0).finish()
+    }
+}
 
-        fmt_impls!($name: Binary, Debug, Display, LowerExp, LowerHex, Octal, UpperExp, UpperHex);
+#[cfg(feature = "bytemuck")]
+// SAFETY: `DynEndian` is a transparent wrapper around an integer.
+unsafe impl<T: Primitive> bytemuck::Zeroable for DynEndian<T> {}
+
+#[cfg(feature = "bytemuck")]
+// SAFETY: `DynEndian` is a transparent wrapper around an integer.
+unsafe impl<T: Primitive> bytemuck::Pod for DynEndian<T> {}
+
+/// Reads a `T` stored in `endianness` byte order from the start of `bytes`
+///
+/// Returns `None` if `bytes` is too short.
+#[inline]
+pub fn read<T: Primitive>(bytes: &[u8], endianness: Endianness) -> Option<T> {
+    let bytes = bytes.get(..mem::size_of::<T>())?;
+    // SAFETY: `T::Bits` has no invalid bit patterns, and we've just checked that `bytes` is
+    // large enough.
+    let bits = unsafe { bytes.as_ptr().cast::<T::Bits>().read_unaligned() };
+    Some(endianness.decode(bits))
+}
 
-        impl From<$type> for $name {
-            fn from(val: $type) -> Self {
-                Self::new(val)
-            }
+/// Writes `value` to the start of `bytes`, in `endianness` byte order
+///
+/// Returns `None` if `bytes` is too short.
+#[inline]
+pub fn write<T: Primitive>(bytes: &mut [u8], value: T, endianness: Endianness) -> Option<()> {
+    let bytes = bytes.get_mut(..mem::size_of::<T>())?;
+    // SAFETY: We've just checked that `bytes` is large enough.
+    unsafe {
+        bytes
+            .as_mut_ptr()
+            .cast::<T::Bits>()
+            .write_unaligned(endianness.encode(value));
+    }
+    Some(())
+}
+
+macro_rules! read_write_fns {
+    ($($read:ident, $write:ident: $type:ty;)*) => {$(
+        #[doc = concat!("Reads a `", stringify!($type), "` stored in `endianness` byte order from the start of `bytes`")]
+        #[inline]
+        pub fn $read(bytes: &[u8], endianness: Endianness) -> Option<$type> {
+            read(bytes, endianness)
         }
 
-        impl From<$name> for $type {
-            fn from(val: $name) -> $type {
-                val.get()
-            }
+        #[doc = concat!("Writes a `", stringify!($type), "` to the start of `bytes`, in `endianness` byte order")]
+        #[inline]
+        pub fn $write(bytes: &mut [u8], value: $type, endianness: Endianness) -> Option<()> {
+            write(bytes, value, endianness)
         }
+    )*};
+}
 
+read_write_fns! {
+    read_u16, write_u16: u16;
+    read_u32, write_u32: u32;
+    read_u64, write_u64: u64;
+    read_u128, write_u128: u128;
+    read_i16, write_i16: i16;
+    read_i32, write_i32: i32;
+    read_i64, write_i64: i64;
+    read_i128, write_i128: i128;
+    read_f32, write_f32: f32;
+    read_f64, write_f64: f64;
+}
 
-        // $name op $name
+/// A plain-old-data type with a native-endian counterpart
+///
+/// This is normally implemented for structures of endian types with
+/// `#[derive(EndianStruct)]`, which generates the native structure and checks the safety
+/// requirements at compile time.
+///
+/// # Safety
+///
+/// Every bit pattern must be a valid `Self`, and `Self` must not contain any padding.
+pub unsafe trait EndianStruct: Copy + 'static {
+    /// The native-endian counterpart of `Self`
+    type Native: Copy;
+
+    fn to_native(self) -> Self::Native;
+
+    fn from_native(native: Self::Native) -> Self;
+
+    /// Reinterprets the start of `bytes` as a `Self`
+    ///
+    /// Returns `None` if `bytes` is too short or not suitably aligned.
+    #[inline]
+    fn from_bytes(bytes: &[u8]) -> Option<&Self> {
+        if bytes.len() < mem::size_of::<Self>()
+            || bytes.as_ptr().align_offset(mem::align_of::<Self>()) != 0
+        {
+            return None;
+        }
+        // SAFETY: We've checked the size and alignment, and the trait's safety requirements
+        // guarantee that any bytes are a valid `Self`.
+        Some(unsafe { &*bytes.as_ptr().cast::<Self>() })
+    }
+
+    /// Reinterprets the start of `bytes` as a `Self`
+    ///
+    /// Returns `None` if `bytes` is too short or not suitably aligned.
+    #[inline]
+    fn from_bytes_mut(bytes: &mut [u8]) -> Option<&mut Self> {
+        if bytes.len() < mem::size_of::<Self>()
+            || bytes.as_ptr().align_offset(mem::align_of::<Self>()) != 0
+        {
+            return None;
+        }
+        // SAFETY: We've checked the size and alignment, and the trait's safety requirements
+        // guarantee that any bytes are a valid `Self`, and that any `Self` is valid bytes.
+        Some(unsafe { &mut *bytes.as_mut_ptr().cast::<Self>() })
+    }
+}
 
+#[cfg(feature = "derive")]
+pub use libsa_derive::EndianStruct;
 
-        impl ops::Add for $name {
-            type Output = Self;
+// SAFETY: Primitives have no padding or invalid bit patterns.
+unsafe impl<T: Primitive> EndianStruct for T {
+    type Native = T;
 
-            #[inline(always)]
-            fn add(self, rhs: Self) -> Self {
-                Self::new(self.get() + rhs.get())
-            }
-        }
+    #[inline(always)]
+    fn to_native(self) -> T {
+        self
+    }
 
-        impl ops::BitAnd for $name {
-            type Output = Self;
+    #[inline(always)]
+    fn from_native(native: T) -> Self {
+        native
+    }
+}
 
-            #[inline(always)]
-            fn bitand(self, rhs: Self) -> Self {
-                Self(self.0 & rhs.0)
-            }
-        }
+// SAFETY: `Endian` is a transparent wrapper around an integer.
+unsafe impl<T: Primitive, O: ByteOrder> EndianStruct for Endian<T, O> {
+    type Native = T;
 
-        impl ops::BitOr for $name {
-            type Output = Self;
+    #[inline(always)]
+    fn to_native(self) -> T {
+        self.get()
+    }
 
-            #[inline(always)]
-            fn bitor(self, rhs: Self) -> Self {
-                Self(self.0 | rhs.0)
-            }
-        }
+    #[inline(always)]
+    fn from_native(native: T) -> Self {
+        Self::new(native)
+    }
+}
 
-        impl ops::BitXor for $name {
-            type Output = Self;
+// SAFETY: `Unaligned` is a transparent wrapper around a byte array.
+unsafe impl<T: Primitive, O: ByteOrder> EndianStruct for Unaligned<T, O> {
+    type Native = T;
 
-            #[inline(always)]
-            fn bitxor(self, rhs: Self) -> Self {
-                Self(self.0 ^ rhs.0)
-            }
-        }
-
-        impl ops::Div for $name {
-            type Output = Self;
+    #[inline(always)]
+    fn to_native(self) -> T {
+        self.get()
+    }
 
-            #[inline(always)]
-            fn div(self, rhs: Self) -> Self {
-                Self::new(self.get() / rhs.get())
-            }
-        }
+    #[inline(always)]
+    fn from_native(native: T) -> Self {
+        Self::new(native)
+    }
+}
 
-        impl ops::Mul for $name {
-            type Output = Self;
+// SAFETY: Arrays have no padding between elements, and `T` upholds the requirements.
+unsafe impl<T: EndianStruct, const N: usize> EndianStruct for [T; N] {
+    type Native = [T::Native; N];
 
-            #[inline(always)]
-            fn mul(self, rhs: Self) -> Self {
-                Self::new(self.get() * rhs.get())
-            }
-        }
+    #[inline]
+    fn to_native(self) -> Self::Native {
+        self.map(T::to_native)
+    }
 
-        impl ops::Not for $name {
-            type Output = Self;
+    #[inline]
+    fn from_native(native: Self::Native) -> Self {
+        native.map(T::from_native)
+    }
+}
 
+macro_rules! fmt_impls {
+    ($name:ident: $($fmt:ident),*) => {$(
+        impl<T: Primitive + fmt::$fmt, O: ByteOrder> fmt::$fmt for $name<T, O> {
             #[inline(always)]
-            fn not(self) -> Self {
-                Self(!self.0)
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::$fmt::fmt(&self.get(), f)
             }
         }
+    )*};
+}
 
-        impl ops::Rem for $name {
+macro_rules! op_impls {
+    ($name:ident: $($op:ident::$f:ident, $op_assign:ident::$f_assign:ident;)*) => {$(
+        impl<T: Primitive + ops::$op<Output = T>, O: ByteOrder> ops::$op for $name<T, O> {
             type Output = Self;
 
             #[inline(always)]
-            fn rem(self, rhs: Self) -> Self {
-                Self::new(self.get() % rhs.get())
+            fn $f(self, rhs: Self) -> Self {
+                Self::new(ops::$op::$f(self.get(), rhs.get()))
             }
         }
 
-        impl ops::Shl for $name {
+        impl<T: Primitive + ops::$op<Output = T>, O: ByteOrder> ops::$op<T> for $name<T, O> {
             type Output = Self;
 
             #[inline(always)]
-            fn shl(self, rhs: Self) -> Self {
-                Self::new(self.get() << rhs.get())
+            fn $f(self, rhs: T) -> Self {
+                Self::new(ops::$op::$f(self.get(), rhs))
             }
         }
 
-        impl ops::Shr for $name {
-            type Output = Self;
-
+        impl<T: Primitive + ops::$op<Output = T>, O: ByteOrder> ops::$op_assign for $name<T, O> {
             #[inline(always)]
-            fn shr(self, rhs: Self) -> Self {
-                Self::new(self.get() >> rhs.get())
+            fn $f_assign(&mut self, rhs: Self) {
+                *self = ops::$op::$f(*self, rhs);
             }
         }
 
-        impl ops::Sub for $name {
-            type Output = Self;
-
+        impl<T: Primitive + ops::$op<Output = T>, O: ByteOrder> ops::$op_assign<T> for $name<T, O> {
             #[inline(always)]
-            fn sub(self, rhs: Self) -> Self {
-                Self::new(self.get() - rhs.get())
+            fn $f_assign(&mut self, rhs: T) {
+                *self = ops::$op::$f(*self, rhs);
             }
         }
+    )*};
+}
 
-        op_assign_impl!($name, $name);
-
-        impl PartialOrd for $name {
-            fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
-                Some(self.cmp(other))
-            }
-        }
+/// Implements formatting, operators and comparisons for a wrapper type providing
+/// `new(T)` and `get() -> T`
+macro_rules! endian_impls {
+    ($name:ident) => {
+        fmt_impls!($name: Binary, Debug, Display, LowerExp, LowerHex, Octal, UpperExp, UpperHex);
 
-        impl Ord for $name {
-            fn cmp(&self, other: &Self) -> cmp::Ordering {
-                self.get().cmp(&other.get())
-            }
+        op_impls! {
+            $name:
+            Add::add, AddAssign::add_assign;
+            Div::div, DivAssign::div_assign;
+            Mul::mul, MulAssign::mul_assign;
+            Rem::rem, RemAssign::rem_assign;
+            Shl::shl, ShlAssign::shl_assign;
+            Shr::shr, ShrAssign::shr_assign;
+            Sub::sub, SubAssign::sub_assign;
         }
 
-
-        // $name op $type
-
-
-        impl ops::Add<$type> for $name {
+        impl<T: Primitive + ops::Neg<Output = T>, O: ByteOrder> ops::Neg for $name<T, O> {
             type Output = Self;
 
             #[inline(always)]
-            fn add(self, rhs: $type) -> Self {
-                Self::new(self.get() + rhs)
+            fn neg(self) -> Self {
+                Self::new(-self.get())
             }
         }
 
-        impl ops::BitAnd<$type> for $name {
-            type Output = Self;
-
+        impl<T: Primitive, O: ByteOrder> From<T> for $name<T, O> {
             #[inline(always)]
-            fn bitand(self, rhs: $type) -> Self {
-                Self::new(self.get() & rhs)
+            fn from(val: T) -> Self {
+                Self::new(val)
             }
         }
 
-        impl ops::BitOr<$type> for $name {
-            type Output = Self;
-
+        impl<T: Primitive + PartialEq, O: ByteOrder> PartialEq for $name<T, O> {
             #[inline(always)]
-            fn bitor(self, rhs: $type) -> Self {
-                Self::new(self.get() | rhs)
+            fn eq(&self, other: &Self) -> bool {
+                self.get() == other.get()
             }
         }
 
-        impl ops::BitXor<$type> for $name {
-            type Output = Self;
+        impl<T: Primitive + Eq, O: ByteOrder> Eq for $name<T, O> {}
 
+        impl<T: Primitive + hash::Hash, O: ByteOrder> hash::Hash for $name<T, O> {
             #[inline(always)]
-            fn bitxor(self, rhs: $type) -> Self {
-                Self::new(self.get() ^ rhs)
+            fn hash<H: hash::Hasher>(&self, state: &mut H) {
+                self.get().hash(state);
             }
         }
 
-        impl ops::Div<$type> for $name {
-            type Output = Self;
-
+        impl<T: Primitive + PartialOrd, O: ByteOrder> PartialOrd for $name<T, O> {
             #[inline(always)]
-            fn div(self, rhs: $type) -> Self {
-                Self::new(self.get() / rhs)
+            fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+                self.get().partial_cmp(&other.get())
             }
         }
 
-        impl ops::Mul<$type> for $name {
-            type Output = Self;
-
+        impl<T: Primitive + Ord, O: ByteOrder> Ord for $name<T, O> {
             #[inline(always)]
-            fn mul(self, rhs: $type) -> Self {
-                Self::new(self.get() * rhs)
+            fn cmp(&self, other: &Self) -> cmp::Ordering {
+                self.get().cmp(&other.get())
             }
         }
 
-        impl ops::Rem<$type> for $name {
-            type Output = Self;
+        impl<T: Primitive + PartialEq, O: ByteOrder> PartialEq<T> for $name<T, O> {
+            #[inline(always)]
+            fn eq(&self, other: &T) -> bool {
+                self.get() == *other
+            }
+        }
 
+        impl<T: Primitive + PartialOrd, O: ByteOrder> PartialOrd<T> for $name<T, O> {
             #[inline(always)]
-            fn rem(self, rhs: $type) -> Self {
-                Self::new(self.get() % rhs)
+            fn partial_cmp(&self, other: &T) -> Option<cmp::Ordering> {
+                self.get().partial_cmp(other)
             }
         }
+    };
+}
+
+endian_impls!(Endian);
+endian_impls!(Unaligned);
 
-        impl ops::Shl<$type> for $name {
+/// Implements the bitwise operators on the stored representation
+///
+/// Each byte of the result depends only on the same byte of the operands, so no conversion
+/// to or from the native byte order is needed.
+macro_rules! bit_op_impls {
+    ($($op:ident::$f:ident, $op_assign:ident::$f_assign:ident;)*) => {$(
+        impl<T: Primitive + ops::$op<Output = T>, O: ByteOrder> ops::$op for Endian<T, O> {
             type Output = Self;
 
             #[inline(always)]
-            fn shl(self, rhs: $type) -> Self {
-                Self::new(self.get() << rhs)
+            fn $f(self, rhs: Self) -> Self {
+                Self::from_raw(ops::$op::$f(self.bits, rhs.bits))
             }
         }
 
-        impl ops::Shr<$type> for $name {
+        impl<T: Primitive + ops::$op<Output = T>, O: ByteOrder> ops::$op for Unaligned<T, O> {
             type Output = Self;
 
             #[inline(always)]
-            fn shr(self, rhs: $type) -> Self {
-                Self::new(self.get() >> rhs)
+            fn $f(self, rhs: Self) -> Self {
+                let mut bytes = self.bytes;
+                for (byte, rhs) in bytes.as_mut().iter_mut().zip(rhs.bytes.as_ref()) {
+                    *byte = ops::$op::$f(*byte, *rhs);
+                }
+                Self::from_bytes(bytes)
             }
         }
 
-        impl ops::Sub<$type> for $name {
+        bit_op_impls!(@common Endian, $op::$f, $op_assign::$f_assign);
+        bit_op_impls!(@common Unaligned, $op::$f, $op_assign::$f_assign);
+    )*};
+
+    (@common $name:ident, $op:ident::$f:ident, $op_assign:ident::$f_assign:ident) => {
+        impl<T: Primitive, O: ByteOrder> ops::$op<T> for $name<T, O>
+        where
+            Self: ops::$op<Output = Self>,
+        {
             type Output = Self;
 
             #[inline(always)]
-            fn sub(self, rhs: $type) -> Self {
-                Self::new(self.get() - rhs)
+            fn $f(self, rhs: T) -> Self {
+                ops::$op::$f(self, Self::new(rhs))
             }
         }
 
-        op_assign_impl!($name, $type);
-
-        impl PartialEq<$type> for $name {
-            fn eq(&self, other: &$type) -> bool {
-                self.get() == *other
+        impl<T: Primitive, O: ByteOrder> ops::$op_assign for $name<T, O>
+        where
+            Self: ops::$op<Output = Self>,
+        {
+            #[inline(always)]
+            fn $f_assign(&mut self, rhs: Self) {
+                *self = ops::$op::$f(*self, rhs);
             }
         }
 
-        impl PartialOrd<$type> for $name {
-            fn partial_cmp(&self, other: &$type) -> Option<cmp::Ordering> {
-                Some(self.get().cmp(other))
+        impl<T: Primitive, O: ByteOrder> ops::$op_assign<T> for $name<T, O>
+        where
+            Self: ops::$op<Output = Self>,
+        {
+            #[inline(always)]
+            fn $f_assign(&mut self, rhs: T) {
+                *self = ops::$op::$f(*self, Self::new(rhs));
             }
         }
+    };
+}
 
+bit_op_impls! {
+    BitAnd::bitand, BitAndAssign::bitand_assign;
+    BitOr::bitor, BitOrAssign::bitor_assign;
+    BitXor::bitxor, BitXorAssign::bitxor_assign;
+}
 
-        // $type op $name
+impl<T: Primitive + ops::Not<Output = T>, O: ByteOrder> ops::Not for Endian<T, O> {
+    type Output = Self;
 
+    #[inline(always)]
+    fn not(self) -> Self {
+        Self::from_raw(!self.bits)
+    }
+}
 
-        impl ops::Add<$name> for $type {
-            type Output = Self;
+impl<T: Primitive + ops::Not<Output = T>, O: ByteOrder> ops::Not for Unaligned<T, O> {
+    type Output = Self;
 
-            #[inline(always)]
-            fn add(self, rhs: $name) -> Self {
-                self + rhs.get()
-            }
+    #[inline(always)]
+    fn not(self) -> Self {
+        let mut bytes = self.bytes;
+        for byte in bytes.as_mut() {
+            *byte = !*byte;
         }
+        Self::from_bytes(bytes)
+    }
+}
 
-        impl ops::BitAnd<$name> for $type {
+macro_rules! primitive_op_impls {
+    ($name:ident, $type:ty: $($op:ident::$f:ident, $op_assign:ident::$f_assign:ident;)*) => {$(
+        impl<O: ByteOrder> ops::$op<$name<$type, O>> for $type {
             type Output = Self;
 
             #[inline(always)]
-            fn bitand(self, rhs: $name) -> Self {
-                self & rhs.get()
+            fn $f(self, rhs: $name<$type, O>) -> Self {
+                ops::$op::$f(self, rhs.get())
             }
         }
 
-        impl ops::BitOr<$name> for $type {
-            type Output = Self;
-
+        impl<O: ByteOrder> ops::$op_assign<$name<$type, O>> for $type {
             #[inline(always)]
-            fn bitor(self, rhs: $name) -> Self {
-                self | rhs.get()
+            fn $f_assign(&mut self, rhs: $name<$type, O>) {
+                ops::$op_assign::$f_assign(self, rhs.get());
             }
         }
+    )*};
+}
 
-        impl ops::BitXor<$name> for $type {
-            type Output = Self;
+/// Implements the traits which must name the primitive type directly, i.e. `$type op $name`
+macro_rules! primitive_impls {
+    ($name:ident: $($type:ty),*) => {$(
+        primitive_op_impls! {
+            $name, $type:
+            Add::add, AddAssign::add_assign;
+            Div::div, DivAssign::div_assign;
+            Mul::mul, MulAssign::mul_assign;
+            Rem::rem, RemAssign::rem_assign;
+            Sub::sub, SubAssign::sub_assign;
+        }
 
+        impl<O: ByteOrder> From<$name<$type, O>> for $type {
             #[inline(always)]
-            fn bitxor(self, rhs: $name) -> Self {
-                self ^ rhs.get()
+            fn from(val: $name<$type, O>) -> $type {
+                val.get()
             }
         }
 
-        impl ops::Div<$name> for $type {
-            type Output = Self;
-
+        impl<O: ByteOrder> PartialEq<$name<$type, O>> for $type {
             #[inline(always)]
-            fn div(self, rhs: $name) -> Self {
-                self / rhs.get()
+            fn eq(&self, other: &$name<$type, O>) -> bool {
+                *self == other.get()
             }
         }
 
-        impl ops::Mul<$name> for $type {
-            type Output = Self;
-
+        impl<O: ByteOrder> PartialOrd<$name<$type, O>> for $type {
             #[inline(always)]
-            fn mul(self, rhs: $name) -> Self {
-                self * rhs.get()
+            fn partial_cmp(&self, other: &$name<$type, O>) -> Option<cmp::Ordering> {
+                self.partial_cmp(&other.get())
             }
         }
 
-        impl ops::Rem<$name> for $type {
-            type Output = Self;
+        impl<O: ByteOrder> $name<$type, O> {
+            pub const MIN: Self = Self::new(<$type>::MIN);
+            pub const MAX: Self = Self::new(<$type>::MAX);
+        }
+    )*};
+}
 
-            #[inline(always)]
-            fn rem(self, rhs: $name) -> Self {
-                self % rhs.get()
-            }
+/// Implements the traits which must name the primitive type directly, for integers
+macro_rules! int_impls {
+    ($name:ident: $($type:ty),*) => {$(
+        primitive_impls!($name: $type);
+
+        primitive_op_impls! {
+            $name, $type:
+            BitAnd::bitand, BitAndAssign::bitand_assign;
+            BitOr::bitor, BitOrAssign::bitor_assign;
+            BitXor::bitxor, BitXorAssign::bitxor_assign;
+            Shl::shl, ShlAssign::shl_assign;
+            Shr::shr, ShrAssign::shr_assign;
         }
 
-        impl ops::Shl<$name> for $type {
-            type Output = Self;
+        impl<O: ByteOrder> $name<$type, O> {
+            pub const BITS: u32 = <$type>::BITS;
 
-            #[inline(always)]
-            fn shl(self, rhs: $name) -> Self {
-                self << rhs.get()
+            int_methods! {
+                $type;
+
+                checked_add(rhs: Self) -> Option<Self>;
+                checked_sub(rhs: Self) -> Option<Self>;
+                checked_mul(rhs: Self) -> Option<Self>;
+                checked_div(rhs: Self) -> Option<Self>;
+                checked_rem(rhs: Self) -> Option<Self>;
+                checked_neg() -> Option<Self>;
+                checked_shl(rhs: u32) -> Option<Self>;
+                checked_shr(rhs: u32) -> Option<Self>;
+                checked_pow(exp: u32) -> Option<Self>;
+
+                wrapping_add(rhs: Self) -> Self;
+                wrapping_sub(rhs: Self) -> Self;
+                wrapping_mul(rhs: Self) -> Self;
+                wrapping_div(rhs: Self) -> Self;
+                wrapping_rem(rhs: Self) -> Self;
+                wrapping_neg() -> Self;
+                wrapping_shl(rhs: u32) -> Self;
+                wrapping_shr(rhs: u32) -> Self;
+                wrapping_pow(exp: u32) -> Self;
+
+                saturating_add(rhs: Self) -> Self;
+                saturating_sub(rhs: Self) -> Self;
+                saturating_mul(rhs: Self) -> Self;
+                saturating_div(rhs: Self) -> Self;
+                saturating_pow(exp: u32) -> Self;
+
+                overflowing_add(rhs: Self) -> (Self, bool);
+                overflowing_sub(rhs: Self) -> (Self, bool);
+                overflowing_mul(rhs: Self) -> (Self, bool);
+                overflowing_div(rhs: Self) -> (Self, bool);
+                overflowing_rem(rhs: Self) -> (Self, bool);
+                overflowing_neg() -> (Self, bool);
+                overflowing_shl(rhs: u32) -> (Self, bool);
+                overflowing_shr(rhs: u32) -> (Self, bool);
+                overflowing_pow(exp: u32) -> (Self, bool);
+
+                pow(exp: u32) -> Self;
+                rotate_left(n: u32) -> Self;
+                rotate_right(n: u32) -> Self;
+                reverse_bits() -> Self;
+
+                count_ones() -> u32;
+                count_zeros() -> u32;
+                leading_zeros() -> u32;
+                trailing_zeros() -> u32;
+                leading_ones() -> u32;
+                trailing_ones() -> u32;
             }
         }
+    )*};
+}
 
-        impl ops::Shr<$name> for $type {
-            type Output = Self;
+/// Forwards inherent methods of the primitive integers, converting arguments and return values
+/// of type `Self`
+macro_rules! int_methods {
+    (@arg Self, $arg:ident) => { $arg.get() };
+    (@arg u32, $arg:ident) => { $arg };
+
+    (@ret Self, $ret:expr) => { Self::new($ret) };
+    (@ret u32, $ret:expr) => { $ret };
+    (@ret Option<Self>, $ret:expr) => {
+        match $ret {
+            Some(value) => Some(Self::new(value)),
+            None => None,
+        }
+    };
+    (@ret (Self, bool), $ret:expr) => {{
+        let (value, overflow) = $ret;
+        (Self::new(value), overflow)
+    }};
 
-            #[inline(always)]
-            fn shr(self, rhs: $name) -> Self {
-                self >> rhs.get()
-            }
+    (
+        $type:ty;
+        $($f:ident($($arg:ident: $arg_ty:tt),*) -> $ret:tt $(<$ret_arg:ident>)?;)*
+    ) => {$(
+        #[doc = concat!("See [`", stringify!($type), "::", stringify!($f), "()`].")]
+        #[inline(always)]
+        #[must_use = "this returns the result of the operation, without modifying the original"]
+        pub const fn $f(self, $($arg: $arg_ty),*) -> $ret $(<$ret_arg>)? {
+            int_methods!(@ret $ret $(<$ret_arg>)?, self.get().$f($(int_methods!(@arg $arg_ty, $arg)),*))
         }
+    )*};
+}
 
-        impl ops::Sub<$name> for $type {
-            type Output = Self;
+macro_rules! endian_primitives {
+    ($impls:ident: $($type:ty = $bits:ty),*) => {
+        $(
+            impl private::Sealed for $type {}
 
-            #[inline(always)]
-            fn sub(self, rhs: $name) -> Self {
-                self - rhs.get()
+            impl Primitive for $type {
+                type Bits = $bits;
+                type Bytes = [u8; mem::size_of::<$type>()];
             }
-        }
+        )*
 
-        op_assign_impl!($type, $name);
+        $impls!(Endian: $($type),*);
+        $impls!(Unaligned: $($type),*);
+    };
+}
 
-        impl PartialEq<$name> for $type {
-            fn eq(&self, other: &$name) -> bool {
-                *self == other.get()
-            }
-        }
+endian_primitives! {
+    int_impls:
+    u8 = u8, u16 = u16, u32 = u32, u64 = u64, u128 = u128, usize = usize,
+    i8 = u8, i16 = u16, i32 = u32, i64 = u64, i128 = u128, isize = usize
+}
 
-        impl PartialOrd<$name> for $type {
-            fn partial_cmp(&self, other: &$name) -> Option<cmp::Ordering> {
-                Some(self.cmp(&other.get()))
-            }
-        }
+endian_primitives! {
+    primitive_impls:
+    f32 = u32, f64 = u64
+}
+
+macro_rules! endian_aliases {
+    ($name:ident<$order:ty>: $(type $long:ident = $short:ident = $type:ty;)*) => {$(
+        pub type $long = $name<$type, $order>;
 
+        #[allow(non_camel_case_types)]
+        pub type $short = $name<$type, $order>;
+    )*};
+    ($name:ident<$order:ty>: $(type $alias:ident = $type:ty;)*) => {$(
+        pub type $alias = $name<$type, $order>;
     )*};
 }
 
-endian_ints! {
-    from: from_le;
-    to:   to_le;
+endian_aliases! {
+    Endian<LittleEndian>:
 
+    type LittleEndianU8    = u8_le    = u8;
     type LittleEndianU16   = u16_le   = u16;
     type LittleEndianU32   = u32_le   = u32;
     type LittleEndianU64   = u64_le   = u64;
+    type LittleEndianU128  = u128_le  = u128;
     type LittleEndianUsize = usize_le = usize;
-}
-
-endian_ints! {
-    signed;
-    from: from_le;
-    to:   to_le;
-
+    type LittleEndianI8    = i8_le    = i8;
     type LittleEndianI16   = i16_le   = i16;
     type LittleEndianI32   = i32_le   = i32;
     type LittleEndianI64   = i64_le   = i64;
+    type LittleEndianI128  = i128_le  = i128;
     type LittleEndianIsize = isize_le = isize;
+    type LittleEndianF32   = f32_le   = f32;
+    type LittleEndianF64   = f64_le   = f64;
 }
 
-endian_ints! {
-    from: from_be;
-    to:   to_be;
+endian_aliases! {
+    Endian<BigEndian>:
 
+    type BigEndianU8    = u8_be    = u8;
     type BigEndianU16   = u16_be   = u16;
     type BigEndianU32   = u32_be   = u32;
     type BigEndianU64   = u64_be   = u64;
+    type BigEndianU128  = u128_be  = u128;
     type BigEndianUsize = usize_be = usize;
-}
-
-endian_ints! {
-    signed;
-    from: from_be;
-    to:   to_be;
-
+    type BigEndianI8    = i8_be    = i8;
     type BigEndianI16   = i16_be   = i16;
     type BigEndianI32   = i32_be   = i32;
     type BigEndianI64   = i64_be   = i64;
+    type BigEndianI128  = i128_be  = i128;
     type BigEndianIsize = isize_be = isize;
+    type BigEndianF32   = f32_be   = f32;
+    type BigEndianF64   = f64_be   = f64;
+}
+
+endian_aliases! {
+    Unaligned<LittleEndian>:
+
+    type U8Le    = u8;
+    type U16Le   = u16;
+    type U32Le   = u32;
+    type U64Le   = u64;
+    type U128Le  = u128;
+    type UsizeLe = usize;
+    type I8Le    = i8;
+    type I16Le   = i16;
+    type I32Le   = i32;
+    type I64Le   = i64;
+    type I128Le  = i128;
+    type IsizeLe = isize;
+    type F32Le   = f32;
+    type F64Le   = f64;
+}
+
+endian_aliases! {
+    Unaligned<BigEndian>:
+
+    type U8Be    = u8;
+    type U16Be   = u16;
+    type U32Be   = u32;
+    type U64Be   = u64;
+    type U128Be  = u128;
+    type UsizeBe = usize;
+    type I8Be    = i8;
+    type I16Be   = i16;
+    type I32Be   = i32;
+    type I64Be   = i64;
+    type I128Be  = i128;
+    type IsizeBe = isize;
+    type F32Be   = f32;
+    type F64Be   = f64;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! round_trip_tests {
+        ($($name:ident: $type:ty = $value:expr;)*) => {$(
+            #[test]
+            #[allow(clippy::float_cmp)]
+            fn $name() {
+                let value: $type = $value;
+
+                let le = Endian::<$type, LittleEndian>::new(value);
+                let be = Endian::<$type, BigEndian>::new(value);
+                assert_eq!(le.get(), value);
+                assert_eq!(be.get(), value);
+                // SAFETY: `Endian<T, O>` is a transparent wrapper around `T::Bits`, which has the
+                // same size as `T`.
+                let (le_bytes, be_bytes) = unsafe {
+                    (
+                        mem::transmute::<Endian<$type, LittleEndian>, [u8; mem::size_of::<$type>()]>(le),
+                        mem::transmute::<Endian<$type, BigEndian>, [u8; mem::size_of::<$type>()]>(be),
+                    )
+                };
+                assert_eq!(le_bytes, value.to_le_bytes());
+                assert_eq!(be_bytes, value.to_be_bytes());
+
+                let le = Unaligned::<$type, LittleEndian>::new(value);
+                let be = Unaligned::<$type, BigEndian>::new(value);
+                assert_eq!(le.get(), value);
+                assert_eq!(be.get(), value);
+                assert_eq!(le.to_bytes(), value.to_le_bytes());
+                assert_eq!(be.to_bytes(), value.to_be_bytes());
+                assert_eq!(Unaligned::<$type, BigEndian>::from_bytes(value.to_be_bytes()).get(), value);
+            }
+        )*};
+    }
+
+    round_trip_tests! {
+        round_trip_u8: u8 = 0xa5;
+        round_trip_u16: u16 = 0x0102;
+        round_trip_u32: u32 = 0x0102_0304;
+        round_trip_u64: u64 = 0x0102_0304_0506_0708;
+        round_trip_u128: u128 = 0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10;
+        round_trip_usize: usize = usize::MAX / 3;
+        round_trip_i8: i8 = -2;
+        round_trip_i16: i16 = -0x0102;
+        round_trip_i32: i32 = -0x0102_0304;
+        round_trip_i64: i64 = -0x0102_0304_0506_0708;
+        round_trip_i128: i128 = -0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10;
+        round_trip_isize: isize = isize::MIN + 1;
+        round_trip_f32: f32 = -1.5;
+        round_trip_f64: f64 = 1.0e-300;
+    }
+
+    #[test]
+    fn bitwise_ops() {
+        let value = u32_be::new(0x1234_5678);
+        assert_eq!(value & u32_be::new(0xff00), 0x5600);
+        assert_eq!(value | 0xf, 0x1234_567f);
+        assert_eq!(value ^ value, 0);
+        assert_eq!((!value).to_raw(), !value.to_raw());
+
+        let value = U32Be::new(0x1234_5678);
+        assert_eq!(value & U32Be::new(0xff00), 0x5600);
+        assert_eq!(!value, !0x1234_5678_u32);
+
+        let mut value = i16_le::new(-2);
+        value ^= -1;
+        assert_eq!(value, 1);
+    }
+
+    #[test]
+    fn float_nan_payload() {
+        let bits = 0x7fa0_0001_u32;
+        let value = f32::from_bits(bits);
+        assert_eq!(f32_be::new(value).get().to_bits(), bits);
+        assert_eq!(f32_le::new(value).get().to_bits(), bits);
+        assert_eq!(F32Be::new(value).to_bytes(), bits.to_be_bytes());
+
+        let bits = 0xfff0_0000_dead_beef_u64;
+        let value = f64::from_bits(bits);
+        assert_eq!(f64_be::new(value).get().to_bits(), bits);
+        assert_eq!(F64Le::from_bytes(bits.to_le_bytes()).get().to_bits(), bits);
+        assert_eq!(f64_be::new(value).to_raw(), bits.to_be());
+    }
+
+    #[test]
+    fn checked_ops() {
+        assert_eq!(u8_be::new(u8::MAX).checked_add(u8_be::new(1)), None);
+        assert_eq!(u16_be::new(1).checked_sub(u16_be::new(2)), None);
+        assert_eq!(u32_le::new(7).checked_div(u32_le::new(0)), None);
+        assert_eq!(i32_be::new(i32::MIN).checked_neg(), None);
+        assert_eq!(i64_le::new(i64::MIN).checked_div(i64_le::new(-1)), None);
+        assert_eq!(u32_be::new(1).checked_shl(32), None);
+        assert_eq!(u16_le::new(2).checked_pow(16), None);
+        assert_eq!(
+            u32_be::new(40).checked_add(u32_be::new(2)),
+            Some(u32_be::new(42))
+        );
+    }
+
+    #[test]
+    fn wrapping_ops() {
+        assert_eq!(
+            u8_be::new(u8::MAX).wrapping_add(u8_be::new(1)),
+            u8_be::new(0)
+        );
+        assert_eq!(
+            u32_be::new(0).wrapping_sub(u32_be::new(1)),
+            u32_be::new(u32::MAX)
+        );
+        assert_eq!(i16_le::new(i16::MIN).wrapping_neg(), i16_le::new(i16::MIN));
+        assert_eq!(u64_be::new(1).wrapping_shl(65), u64_be::new(2));
+        assert_eq!(
+            i8_be::new(i8::MIN).wrapping_div(i8_be::new(-1)),
+            i8_be::new(i8::MIN)
+        );
+    }
+
+    #[test]
+    fn overflowing_ops() {
+        assert_eq!(
+            u16_be::new(u16::MAX).overflowing_add(u16_be::new(2)),
+            (u16_be::new(1), true)
+        );
+        assert_eq!(
+            i32_le::new(i32::MAX).overflowing_mul(i32_le::new(2)),
+            (i32_le::new(-2), true)
+        );
+        assert_eq!(
+            u128_be::new(1).overflowing_shl(129),
+            (u128_be::new(2), true)
+        );
+        assert_eq!(
+            u32_be::new(3).overflowing_sub(u32_be::new(1)),
+            (u32_be::new(2), false)
+        );
+    }
+
+    #[test]
+    fn nonzero_niche() {
+        assert_eq!(mem::size_of::<Option<NonZeroU32Le>>(), 4);
+        assert_eq!(mem::size_of::<Option<NonZeroU64Be>>(), 8);
+        assert!(NonZeroU32Le::new(0).is_none());
+        assert_eq!(
+            NonZeroU32Le::new(5).map(NonZeroU32Le::get),
+            core::num::NonZeroU32::new(5)
+        );
+    }
 }
@@ -0,0 +1,502 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Atomic integers stored in a fixed byte order
+
+use super::{BigEndian, ByteOrder, Endian, LittleEndian, Primitive};
+use core::{
+    fmt,
+    marker::PhantomData,
+    ptr,
+    sync::atomic::{self, Ordering},
+};
+
+mod raw {
+    use core::sync::atomic::Ordering;
+
+    /// The operations of the `core::sync::atomic` integer types
+    pub trait RawAtomic: Send + Sync {
+        type Bits: Copy;
+
+        fn load(&self, order: Ordering) -> Self::Bits;
+        fn store(&self, bits: Self::Bits, order: Ordering);
+        fn swap(&self, bits: Self::Bits, order: Ordering) -> Self::Bits;
+        fn compare_exchange(
+            &self,
+            current: Self::Bits,
+            new: Self::Bits,
+            success: Ordering,
+            failure: Ordering,
+        ) -> Result<Self::Bits, Self::Bits>;
+        fn compare_exchange_weak(
+            &self,
+            current: Self::Bits,
+            new: Self::Bits,
+            success: Ordering,
+            failure: Ordering,
+        ) -> Result<Self::Bits, Self::Bits>;
+        fn fetch_add(&self, bits: Self::Bits, order: Ordering) -> Self::Bits;
+        fn fetch_sub(&self, bits: Self::Bits, order: Ordering) -> Self::Bits;
+        fn fetch_and(&self, bits: Self::Bits, order: Ordering) -> Self::Bits;
+        fn fetch_or(&self, bits: Self::Bits, order: Ordering) -> Self::Bits;
+        fn fetch_xor(&self, bits: Self::Bits, order: Ordering) -> Self::Bits;
+        fn get_mut(&mut self) -> &mut Self::Bits;
+    }
+}
+
+/// A primitive integer with an atomic counterpart
+///
+/// This trait is sealed and implemented only for the primitive integer types which have
+/// atomic operations on the target.
+pub trait AtomicPrimitive: Primitive {
+    #[doc(hidden)]
+    type Atomic: raw::RawAtomic<Bits = Self::Bits>;
+}
+
+/// An atomic `T` stored in `O` byte order
+///
+/// This has the same size and bit pattern as [`Endian<T, O>`], and converts to and from the
+/// native byte order on each access.
+#[repr(transparent)]
+pub struct Atomic<T: AtomicPrimitive, O> {
+    atomic: T::Atomic,
+    order: PhantomData<O>,
+}
+
+impl<T: AtomicPrimitive, O: ByteOrder> Atomic<T, O> {
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        let bits = Endian::<T, O>::new(value).to_raw();
+        Self {
+            // SAFETY: The atomic integer types have the same size and bit validity as their
+            // underlying integer, though possibly a greater alignment.
+            atomic: unsafe { ptr::addr_of!(bits).cast::<T::Atomic>().read_unaligned() },
+            order: PhantomData,
+        }
+    }
+
+    /// Returns a mutable reference to the underlying value
+    ///
+    /// This is safe because the mutable reference guarantees no other threads are
+    /// concurrently accessing the value.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut Endian<T, O> {
+        let bits: *mut T::Bits = raw::RawAtomic::get_mut(&mut self.atomic);
+        // SAFETY: `Endian<T, O>` is a transparent wrapper around `T::Bits`.
+        unsafe { &mut *bits.cast::<Endian<T, O>>() }
+    }
+
+    #[inline]
+    pub fn into_inner(mut self) -> Endian<T, O> {
+        *self.get_mut()
+    }
+
+    #[inline]
+    pub fn load(&self, order: Ordering) -> T {
+        Endian::<T, O>::from_raw(raw::RawAtomic::load(&self.atomic, order)).get()
+    }
+
+    #[inline]
+    pub fn store(&self, value: T, order: Ordering) {
+        raw::RawAtomic::store(&self.atomic, Endian::<T, O>::new(value).to_raw(), order);
+    }
+
+    #[inline]
+    pub fn swap(&self, value: T, order: Ordering) -> T {
+        let bits = raw::RawAtomic::swap(&self.atomic, Endian::<T, O>::new(value).to_raw(), order);
+        Endian::<T, O>::from_raw(bits).get()
+    }
+
+    /// Stores `new` if the current value is equal to `current`
+    ///
+    /// # Errors
+    ///
+    /// Returns the current value if it was not equal to `current`.
+    #[inline]
+    pub fn compare_exchange(
+        &self,
+        current: T,
+        new: T,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<T, T> {
+        raw::RawAtomic::compare_exchange(
+            &self.atomic,
+            Endian::<T, O>::new(current).to_raw(),
+            Endian::<T, O>::new(new).to_raw(),
+            success,
+            failure,
+        )
+        .map(|bits| Endian::<T, O>::from_raw(bits).get())
+        .map_err(|bits| Endian::<T, O>::from_raw(bits).get())
+    }
+
+    /// Stores `new` if the current value is equal to `current`
+    ///
+    /// Unlike [`compare_exchange()`](Self::compare_exchange), this function is allowed to
+    /// spuriously fail even when the comparison succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns the current value if it was not equal to `current`, or on spurious failure.
+    #[inline]
+    pub fn compare_exchange_weak(
+        &self,
+        current: T,
+        new: T,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<T, T> {
+        raw::RawAtomic::compare_exchange_weak(
+            &self.atomic,
+            Endian::<T, O>::new(current).to_raw(),
+            Endian::<T, O>::new(new).to_raw(),
+            success,
+            failure,
+        )
+        .map(|bits| Endian::<T, O>::from_raw(bits).get())
+        .map_err(|bits| Endian::<T, O>::from_raw(bits).get())
+    }
+
+    /// Repeatedly applies `f` to the current value until it is successfully stored
+    ///
+    /// # Errors
+    ///
+    /// Returns the current value if `f` returns `None`.
+    #[inline]
+    pub fn fetch_update(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        mut f: impl FnMut(T) -> Option<T>,
+    ) -> Result<T, T> {
+        let mut prev = self.load(fetch_order);
+        while let Some(next) = f(prev) {
+            match self.compare_exchange_weak(prev, next, set_order, fetch_order) {
+                Ok(value) => return Ok(value),
+                Err(value) => prev = value,
+            }
+        }
+        Err(prev)
+    }
+
+    /// Bitwise AND with the current value, returning the previous value
+    ///
+    /// `value` is converted to `O` byte order and applied to the stored bits directly.
+    #[inline]
+    pub fn fetch_and(&self, value: T, order: Ordering) -> T {
+        let bits = Endian::<T, O>::new(value).to_raw();
+        Endian::<T, O>::from_raw(raw::RawAtomic::fetch_and(&self.atomic, bits, order)).get()
+    }
+
+    /// Bitwise OR with the current value, returning the previous value
+    ///
+    /// `value` is converted to `O` byte order and applied to the stored bits directly.
+    #[inline]
+    pub fn fetch_or(&self, value: T, order: Ordering) -> T {
+        let bits = Endian::<T, O>::new(value).to_raw();
+        Endian::<T, O>::from_raw(raw::RawAtomic::fetch_or(&self.atomic, bits, order)).get()
+    }
+
+    /// Bitwise XOR with the current value, returning the previous value
+    ///
+    /// `value` is converted to `O` byte order and applied to the stored bits directly.
+    #[inline]
+    pub fn fetch_xor(&self, value: T, order: Ordering) -> T {
+        let bits = Endian::<T, O>::new(value).to_raw();
+        Endian::<T, O>::from_raw(raw::RawAtomic::fetch_xor(&self.atomic, bits, order)).get()
+    }
+}
+
+impl<T: AtomicPrimitive + fmt::Debug, O: ByteOrder> fmt::Debug for Atomic<T, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.load(Ordering::Relaxed), f)
+    }
+}
+
+impl<T: AtomicPrimitive + Default, O: ByteOrder> Default for Atomic<T, O> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: AtomicPrimitive, O: ByteOrder> From<T> for Atomic<T, O> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: AtomicPrimitive, O: ByteOrder> From<Endian<T, O>> for Atomic<T, O> {
+    fn from(value: Endian<T, O>) -> Self {
+        Self::new(value.get())
+    }
+}
+
+macro_rules! atomic_ints {
+    ($($width:literal: $atomic:ident = $bits:ty: $($type:ty),*;)*) => {$(
+        #[cfg(target_has_atomic = $width)]
+        impl raw::RawAtomic for atomic::$atomic {
+            type Bits = $bits;
+
+            #[inline(always)]
+            fn load(&self, order: Ordering) -> $bits {
+                self.load(order)
+            }
+
+            #[inline(always)]
+            fn store(&self, bits: $bits, order: Ordering) {
+                self.store(bits, order);
+            }
+
+            #[inline(always)]
+            fn swap(&self, bits: $bits, order: Ordering) -> $bits {
+                self.swap(bits, order)
+            }
+
+            #[inline(always)]
+            fn compare_exchange(
+                &self,
+                current: $bits,
+                new: $bits,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$bits, $bits> {
+                self.compare_exchange(current, new, success, failure)
+            }
+
+            #[inline(always)]
+            fn compare_exchange_weak(
+                &self,
+                current: $bits,
+                new: $bits,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$bits, $bits> {
+                self.compare_exchange_weak(current, new, success, failure)
+            }
+
+            #[inline(always)]
+            fn fetch_add(&self, bits: $bits, order: Ordering) -> $bits {
+                self.fetch_add(bits, order)
+            }
+
+            #[inline(always)]
+            fn fetch_sub(&self, bits: $bits, order: Ordering) -> $bits {
+                self.fetch_sub(bits, order)
+            }
+
+            #[inline(always)]
+            fn fetch_and(&self, bits: $bits, order: Ordering) -> $bits {
+                self.fetch_and(bits, order)
+            }
+
+            #[inline(always)]
+            fn fetch_or(&self, bits: $bits, order: Ordering) -> $bits {
+                self.fetch_or(bits, order)
+            }
+
+            #[inline(always)]
+            fn fetch_xor(&self, bits: $bits, order: Ordering) -> $bits {
+                self.fetch_xor(bits, order)
+            }
+
+            #[inline(always)]
+            fn get_mut(&mut self) -> &mut $bits {
+                self.get_mut()
+            }
+        }
+
+        $(
+            #[cfg(target_has_atomic = $width)]
+            impl AtomicPrimitive for $type {
+                type Atomic = atomic::$atomic;
+            }
+
+            #[cfg(target_has_atomic = $width)]
+            impl<O: ByteOrder> Atomic<$type, O> {
+                /// Adds to the current value, returning the previous value
+                ///
+                /// This operation wraps around on overflow.
+                #[inline]
+                pub fn fetch_add(&self, value: $type, order: Ordering) -> $type {
+                    if O::ENDIANNESS.is_native() {
+                        let bits = Endian::<$type, O>::new(value).to_raw();
+                        let prev = raw::RawAtomic::fetch_add(&self.atomic, bits, order);
+                        Endian::<$type, O>::from_raw(prev).get()
+                    } else {
+                        self.fetch_modify(order, |prev| prev.wrapping_add(value))
+                    }
+                }
+
+                /// Subtracts from the current value, returning the previous value
+                ///
+                /// This operation wraps around on overflow.
+                #[inline]
+                pub fn fetch_sub(&self, value: $type, order: Ordering) -> $type {
+                    if O::ENDIANNESS.is_native() {
+                        let bits = Endian::<$type, O>::new(value).to_raw();
+                        let prev = raw::RawAtomic::fetch_sub(&self.atomic, bits, order);
+                        Endian::<$type, O>::from_raw(prev).get()
+                    } else {
+                        self.fetch_modify(order, |prev| prev.wrapping_sub(value))
+                    }
+                }
+
+                /// Stores the maximum of the current value and `value`, returning the previous
+                /// value
+                #[inline]
+                pub fn fetch_max(&self, value: $type, order: Ordering) -> $type {
+                    self.fetch_modify(order, |prev| prev.max(value))
+                }
+
+                /// Stores the minimum of the current value and `value`, returning the previous
+                /// value
+                #[inline]
+                pub fn fetch_min(&self, value: $type, order: Ordering) -> $type {
+                    self.fetch_modify(order, |prev| prev.min(value))
+                }
+
+                #[inline]
+                fn fetch_modify(&self, order: Ordering, mut f: impl FnMut($type) -> $type) -> $type {
+                    let fetch_order = match order {
+                        Ordering::Release | Ordering::Relaxed => Ordering::Relaxed,
+                        Ordering::Acquire | Ordering::AcqRel => Ordering::Acquire,
+                        _ => Ordering::SeqCst,
+                    };
+                    match self.fetch_update(order, fetch_order, |prev| Some(f(prev))) {
+                        Ok(prev) | Err(prev) => prev,
+                    }
+                }
+            }
+        )*
+    )*};
+}
+
+atomic_ints! {
+    "8":   AtomicU8    = u8:    u8, i8;
+    "16":  AtomicU16   = u16:   u16, i16;
+    "32":  AtomicU32   = u32:   u32, i32;
+    "64":  AtomicU64   = u64:   u64, i64;
+    "ptr": AtomicUsize = usize: usize, isize;
+}
+
+macro_rules! atomic_aliases {
+    ($order:ty: $($width:literal: $(type $alias:ident = $type:ty;)*)*) => {$($(
+        #[cfg(target_has_atomic = $width)]
+        pub type $alias = Atomic<$type, $order>;
+    )*)*};
+}
+
+atomic_aliases! {
+    LittleEndian:
+
+    "8":   type AtomicU8Le    = u8;
+           type AtomicI8Le    = i8;
+    "16":  type AtomicU16Le   = u16;
+           type AtomicI16Le   = i16;
+    "32":  type AtomicU32Le   = u32;
+           type AtomicI32Le   = i32;
+    "64":  type AtomicU64Le   = u64;
+           type AtomicI64Le   = i64;
+    "ptr": type AtomicUsizeLe = usize;
+           type AtomicIsizeLe = isize;
+}
+
+atomic_aliases! {
+    BigEndian:
+
+    "8":   type AtomicU8Be    = u8;
+           type AtomicI8Be    = i8;
+    "16":  type AtomicU16Be   = u16;
+           type AtomicI16Be   = i16;
+    "32":  type AtomicU32Be   = u32;
+           type AtomicI32Be   = i32;
+    "64":  type AtomicU64Be   = u64;
+           type AtomicI64Be   = i64;
+    "ptr": type AtomicUsizeBe = usize;
+           type AtomicIsizeBe = isize;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The byte order which is not native on the host, so that the conversions are exercised
+    #[cfg(target_endian = "little")]
+    type Foreign = BigEndian;
+    #[cfg(target_endian = "big")]
+    type Foreign = LittleEndian;
+
+    #[test]
+    fn fetch_add_carry() {
+        let atomic = Atomic::<u32, Foreign>::new(0x00ff_ffff);
+        assert_eq!(atomic.fetch_add(1, Ordering::SeqCst), 0x00ff_ffff);
+        assert_eq!(atomic.load(Ordering::SeqCst), 0x0100_0000);
+        assert_eq!(atomic.fetch_sub(2, Ordering::SeqCst), 0x0100_0000);
+        assert_eq!(atomic.load(Ordering::SeqCst), 0x00ff_fffe);
+        assert_eq!(atomic.fetch_add(u32::MAX, Ordering::SeqCst), 0x00ff_fffe);
+        assert_eq!(atomic.load(Ordering::SeqCst), 0x00ff_fffd);
+        assert_eq!(atomic.into_inner().to_raw(), 0x00ff_fffd_u32.swap_bytes());
+    }
+
+    #[test]
+    fn fetch_max_signed() {
+        let atomic = Atomic::<i32, Foreign>::new(-5);
+        assert_eq!(atomic.fetch_max(-7, Ordering::SeqCst), -5);
+        assert_eq!(atomic.load(Ordering::SeqCst), -5);
+        assert_eq!(atomic.fetch_max(3, Ordering::SeqCst), -5);
+        assert_eq!(atomic.load(Ordering::SeqCst), 3);
+        assert_eq!(atomic.fetch_min(-256, Ordering::SeqCst), 3);
+        assert_eq!(atomic.load(Ordering::SeqCst), -256);
+    }
+
+    #[test]
+    fn compare_exchange() {
+        let atomic = Atomic::<u32, Foreign>::new(0x1234_5678);
+        assert_eq!(
+            atomic.compare_exchange(0x1234_5678, 1, Ordering::SeqCst, Ordering::SeqCst),
+            Ok(0x1234_5678)
+        );
+        assert_eq!(
+            atomic.compare_exchange(0x1234_5678, 2, Ordering::SeqCst, Ordering::SeqCst),
+            Err(1)
+        );
+        assert_eq!(atomic.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn bitwise() {
+        let atomic = Atomic::<u16, Foreign>::new(0x0ff0);
+        assert_eq!(atomic.fetch_or(0xf000, Ordering::SeqCst), 0x0ff0);
+        assert_eq!(atomic.fetch_and(0xff00, Ordering::SeqCst), 0xfff0);
+        assert_eq!(atomic.fetch_xor(0x0101, Ordering::SeqCst), 0xff00);
+        assert_eq!(atomic.load(Ordering::SeqCst), 0xfe01);
+    }
+}
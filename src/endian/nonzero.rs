@@ -0,0 +1,320 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Non-zero integers stored in a fixed byte order
+
+use super::{transmute, BigEndian, ByteOrder, Endian, LittleEndian, Primitive, Unaligned};
+use core::{cmp, fmt, hash, marker::PhantomData, num};
+
+/// A primitive integer with a non-zero counterpart
+///
+/// This trait is sealed and implemented only for the primitive integer types.
+pub trait NonZeroPrimitive: Primitive {
+    /// The native non-zero type, e.g. [`NonZeroU32`](num::NonZeroU32) for `u32`
+    type NonZero: Copy + 'static;
+    #[doc(hidden)]
+    type NonZeroBits: Copy + 'static;
+}
+
+/// A non-zero `T` stored in `O` byte order
+///
+/// A byte-swapped integer is zero only if the original was, so the zero bit pattern is never
+/// used and `Option<NonZero<T, O>>` has the same size as `T`. This makes it suitable for
+/// fields such as "offset of the next entry, or 0 if there is none".
+///
+/// There is no unaligned counterpart, since the niche requires the value to be stored as a
+/// single integer.
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(
+        zerocopy::TryFromBytes,
+        zerocopy::Immutable,
+        zerocopy::IntoBytes,
+        zerocopy::KnownLayout
+    )
+)]
+pub struct NonZero<T: NonZeroPrimitive, O> {
+    bits: T::NonZeroBits,
+    order: PhantomData<O>,
+}
+
+#[cfg(feature = "bytemuck")]
+// SAFETY: `NonZero` is a transparent wrapper around an integer.
+unsafe impl<T: NonZeroPrimitive, O: ByteOrder> bytemuck::NoUninit for NonZero<T, O> {}
+
+#[cfg(feature = "bytemuck")]
+// SAFETY: `Option<NonZero>` uses zero to represent `None`.
+unsafe impl<T: NonZeroPrimitive, O: ByteOrder> bytemuck::ZeroableInOption for NonZero<T, O> {}
+
+#[cfg(feature = "bytemuck")]
+// SAFETY: `Option<NonZero>` is the same size as `T`, and every bit pattern is valid.
+unsafe impl<T: NonZeroPrimitive, O: ByteOrder> bytemuck::PodInOption for NonZero<T, O> {}
+
+#[cfg(feature = "bytemuck")]
+// SAFETY: Every non-zero bit pattern is a valid `NonZero`, and `Endian` has the same layout.
+unsafe impl<T: NonZeroPrimitive, O: ByteOrder> bytemuck::CheckedBitPattern for NonZero<T, O> {
+    type Bits = Endian<T, O>;
+
+    #[inline(always)]
+    fn is_valid_bit_pattern(bits: &Endian<T, O>) -> bool {
+        Self::from_endian(*bits).is_some()
+    }
+}
+
+/// An error returned when converting a zero value to a [`NonZero`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ZeroError;
+
+impl fmt::Display for ZeroError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("value is zero")
+    }
+}
+
+impl<T: NonZeroPrimitive, O: ByteOrder> NonZero<T, O> {
+    /// Creates a non-zero value if `value` is not zero
+    #[inline(always)]
+    pub const fn new(value: T) -> Option<Self> {
+        Self::from_endian(Endian::new(value))
+    }
+
+    /// Creates a non-zero value without checking whether `value` is zero
+    ///
+    /// # Safety
+    ///
+    /// `value` must not be zero.
+    #[inline(always)]
+    pub const unsafe fn new_unchecked(value: T) -> Self {
+        match Self::new(value) {
+            Some(value) => value,
+            // SAFETY: The caller guarantees that `value` is not zero.
+            None => unsafe { core::hint::unreachable_unchecked() },
+        }
+    }
+
+    /// Creates a non-zero value from its native counterpart
+    #[inline(always)]
+    pub const fn from_native(value: T::NonZero) -> Self {
+        // SAFETY: `T::NonZero` has the same layout as `T`.
+        let value = unsafe { transmute::<T::NonZero, T>(value) };
+        // SAFETY: `T::NonZero` is never zero.
+        unsafe { Self::new_unchecked(value) }
+    }
+
+    /// Returns the value as its native non-zero counterpart
+    #[inline(always)]
+    pub const fn get(self) -> T::NonZero {
+        // SAFETY: `T::NonZero` has the same layout as `T`, and the value is not zero.
+        unsafe { transmute::<T, T::NonZero>(self.to_endian().get()) }
+    }
+
+    /// Returns the value as the plain endian type
+    #[inline(always)]
+    pub const fn to_endian(self) -> Endian<T, O> {
+        // SAFETY: `T::NonZeroBits` has the same layout as `T::Bits`.
+        Endian::from_raw(unsafe { transmute::<T::NonZeroBits, T::Bits>(self.bits) })
+    }
+
+    /// Converts a plain endian value, if it is not zero
+    #[inline(always)]
+    pub const fn from_endian(value: Endian<T, O>) -> Option<Self> {
+        // SAFETY: `Option<T::NonZeroBits>` is guaranteed to have the same layout as
+        // `T::Bits`, with zero representing `None`.
+        let bits = unsafe { transmute::<T::Bits, Option<T::NonZeroBits>>(value.to_raw()) };
+        match bits {
+            Some(bits) => Some(Self {
+                bits,
+                order: PhantomData,
+            }),
+            None => None,
+        }
+    }
+}
+
+impl<T: NonZeroPrimitive, O: ByteOrder> From<NonZero<T, O>> for Endian<T, O> {
+    #[inline(always)]
+    fn from(val: NonZero<T, O>) -> Self {
+        val.to_endian()
+    }
+}
+
+impl<T: NonZeroPrimitive, O: ByteOrder> From<NonZero<T, O>> for Unaligned<T, O> {
+    #[inline(always)]
+    fn from(val: NonZero<T, O>) -> Self {
+        val.to_endian().into()
+    }
+}
+
+impl<T: NonZeroPrimitive, O: ByteOrder> TryFrom<Endian<T, O>> for NonZero<T, O> {
+    type Error = ZeroError;
+
+    #[inline(always)]
+    fn try_from(val: Endian<T, O>) -> Result<Self, ZeroError> {
+        Self::from_endian(val).ok_or(ZeroError)
+    }
+}
+
+impl<T: NonZeroPrimitive, O: ByteOrder> TryFrom<Unaligned<T, O>> for NonZero<T, O> {
+    type Error = ZeroError;
+
+    #[inline(always)]
+    fn try_from(val: Unaligned<T, O>) -> Result<Self, ZeroError> {
+        Self::from_endian(val.into()).ok_or(ZeroError)
+    }
+}
+
+macro_rules! fmt_impls {
+    ($($fmt:ident),*) => {$(
+        impl<T: NonZeroPrimitive + fmt::$fmt, O: ByteOrder> fmt::$fmt for NonZero<T, O> {
+            #[inline(always)]
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::$fmt::fmt(&self.to_endian().get(), f)
+            }
+        }
+    )*};
+}
+
+fmt_impls!(Binary, Debug, Display, LowerHex, Octal, UpperHex);
+
+impl<T: NonZeroPrimitive + PartialEq, O: ByteOrder> PartialEq for NonZero<T, O> {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.to_endian().get() == other.to_endian().get()
+    }
+}
+
+impl<T: NonZeroPrimitive + Eq, O: ByteOrder> Eq for NonZero<T, O> {}
+
+impl<T: NonZeroPrimitive + hash::Hash, O: ByteOrder> hash::Hash for NonZero<T, O> {
+    #[inline(always)]
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.to_endian().get().hash(state);
+    }
+}
+
+impl<T: NonZeroPrimitive + PartialOrd, O: ByteOrder> PartialOrd for NonZero<T, O> {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        self.to_endian().get().partial_cmp(&other.to_endian().get())
+    }
+}
+
+impl<T: NonZeroPrimitive + Ord, O: ByteOrder> Ord for NonZero<T, O> {
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.to_endian().get().cmp(&other.to_endian().get())
+    }
+}
+
+macro_rules! nonzero_ints {
+    ($($type:ty: $nonzero:ident = $bits:ident;)*) => {$(
+        impl NonZeroPrimitive for $type {
+            type NonZero = num::$nonzero;
+            type NonZeroBits = num::$bits;
+        }
+
+        impl<O: ByteOrder> From<num::$nonzero> for NonZero<$type, O> {
+            #[inline(always)]
+            fn from(val: num::$nonzero) -> Self {
+                Self::from_native(val)
+            }
+        }
+
+        impl<O: ByteOrder> From<NonZero<$type, O>> for num::$nonzero {
+            #[inline(always)]
+            fn from(val: NonZero<$type, O>) -> num::$nonzero {
+                val.get()
+            }
+        }
+
+        impl<O: ByteOrder> NonZero<$type, O> {
+            pub const MIN: Self = Self::from_native(num::$nonzero::MIN);
+            pub const MAX: Self = Self::from_native(num::$nonzero::MAX);
+            pub const BITS: u32 = <$type>::BITS;
+        }
+    )*};
+}
+
+nonzero_ints! {
+    u8:    NonZeroU8    = NonZeroU8;
+    u16:   NonZeroU16   = NonZeroU16;
+    u32:   NonZeroU32   = NonZeroU32;
+    u64:   NonZeroU64   = NonZeroU64;
+    u128:  NonZeroU128  = NonZeroU128;
+    usize: NonZeroUsize = NonZeroUsize;
+    i8:    NonZeroI8    = NonZeroU8;
+    i16:   NonZeroI16   = NonZeroU16;
+    i32:   NonZeroI32   = NonZeroU32;
+    i64:   NonZeroI64   = NonZeroU64;
+    i128:  NonZeroI128  = NonZeroU128;
+    isize: NonZeroIsize = NonZeroUsize;
+}
+
+macro_rules! nonzero_aliases {
+    ($order:ty: $(type $alias:ident = $type:ty;)*) => {$(
+        pub type $alias = NonZero<$type, $order>;
+    )*};
+}
+
+nonzero_aliases! {
+    LittleEndian:
+
+    type NonZeroU8Le    = u8;
+    type NonZeroU16Le   = u16;
+    type NonZeroU32Le   = u32;
+    type NonZeroU64Le   = u64;
+    type NonZeroU128Le  = u128;
+    type NonZeroUsizeLe = usize;
+    type NonZeroI8Le    = i8;
+    type NonZeroI16Le   = i16;
+    type NonZeroI32Le   = i32;
+    type NonZeroI64Le   = i64;
+    type NonZeroI128Le  = i128;
+    type NonZeroIsizeLe = isize;
+}
+
+nonzero_aliases! {
+    BigEndian:
+
+    type NonZeroU8Be    = u8;
+    type NonZeroU16Be   = u16;
+    type NonZeroU32Be   = u32;
+    type NonZeroU64Be   = u64;
+    type NonZeroU128Be  = u128;
+    type NonZeroUsizeBe = usize;
+    type NonZeroI8Be    = i8;
+    type NonZeroI16Be   = i16;
+    type NonZeroI32Be   = i32;
+    type NonZeroI64Be   = i64;
+    type NonZeroI128Be  = i128;
+    type NonZeroIsizeBe = isize;
+}
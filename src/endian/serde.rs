@@ -0,0 +1,175 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! `serde` support for the endian types
+//!
+//! Human-readable formats see the logical value, while binary formats see the bytes as they
+//! are stored in memory, as a fixed-size tuple.
+
+use super::{ByteOrder, Endian, NonZero, NonZeroPrimitive, Primitive, Unaligned};
+use core::{fmt, marker::PhantomData, mem};
+use serde::{
+    de::{self, Deserialize, Deserializer, SeqAccess, Visitor},
+    ser::{Serialize, SerializeTuple, Serializer},
+};
+
+impl<T: Primitive + Serialize, O: ByteOrder> Serialize for Unaligned<T, O> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            self.get().serialize(serializer)
+        } else {
+            let bytes = self.to_bytes();
+            let mut tuple = serializer.serialize_tuple(bytes.as_ref().len())?;
+            for byte in bytes.as_ref() {
+                tuple.serialize_element(byte)?;
+            }
+            tuple.end()
+        }
+    }
+}
+
+impl<'de, T: Primitive + Deserialize<'de>, O: ByteOrder> Deserialize<'de> for Unaligned<T, O> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            T::deserialize(deserializer).map(Self::new)
+        } else {
+            deserializer.deserialize_tuple(mem::size_of::<T>(), BytesVisitor(PhantomData))
+        }
+    }
+}
+
+impl<T: Primitive + Serialize, O: ByteOrder> Serialize for Endian<T, O> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Unaligned::from(*self).serialize(serializer)
+    }
+}
+
+impl<'de, T: Primitive + Deserialize<'de>, O: ByteOrder> Deserialize<'de> for Endian<T, O> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Unaligned::deserialize(deserializer).map(Self::from)
+    }
+}
+
+impl<T: NonZeroPrimitive + Serialize, O: ByteOrder> Serialize for NonZero<T, O> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_endian().serialize(serializer)
+    }
+}
+
+impl<'de, T: NonZeroPrimitive + Deserialize<'de>, O: ByteOrder> Deserialize<'de> for NonZero<T, O> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::from_endian(Endian::deserialize(deserializer)?).ok_or_else(|| {
+            de::Error::invalid_value(de::Unexpected::Unsigned(0), &"a non-zero value")
+        })
+    }
+}
+
+/// Deserializes the raw bytes of an endian value
+struct BytesVisitor<T, O>(PhantomData<(T, O)>);
+
+impl<'de, T: Primitive, O: ByteOrder> Visitor<'de> for BytesVisitor<T, O> {
+    type Value = Unaligned<T, O>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} bytes", mem::size_of::<T>())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = T::Bytes::default();
+        for (i, byte) in bytes.as_mut().iter_mut().enumerate() {
+            *byte = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        Ok(Unaligned::from_bytes(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::endian::{u16_be, u32_le, NonZeroU16Be, U32Be};
+    use serde_test::{assert_de_tokens_error, assert_tokens, Configure, Token};
+
+    #[test]
+    fn human_readable() {
+        assert_tokens(
+            &u32_le::new(0x0102_0304).readable(),
+            &[Token::U32(0x0102_0304)],
+        );
+        assert_tokens(
+            &U32Be::new(0x0102_0304).readable(),
+            &[Token::U32(0x0102_0304)],
+        );
+        assert_tokens(&NonZeroU16Be::new(7).unwrap().readable(), &[Token::U16(7)]);
+        assert_de_tokens_error::<serde_test::Readable<NonZeroU16Be>>(
+            &[Token::U16(0)],
+            "invalid value: integer `0`, expected a non-zero value",
+        );
+    }
+
+    #[test]
+    fn binary() {
+        assert_tokens(
+            &u32_le::new(0x0102_0304).compact(),
+            &[
+                Token::Tuple { len: 4 },
+                Token::U8(4),
+                Token::U8(3),
+                Token::U8(2),
+                Token::U8(1),
+                Token::TupleEnd,
+            ],
+        );
+        assert_tokens(
+            &U32Be::new(0x0102_0304).compact(),
+            &[
+                Token::Tuple { len: 4 },
+                Token::U8(1),
+                Token::U8(2),
+                Token::U8(3),
+                Token::U8(4),
+                Token::TupleEnd,
+            ],
+        );
+        assert_tokens(
+            &u16_be::new(0x0506).compact(),
+            &[
+                Token::Tuple { len: 2 },
+                Token::U8(5),
+                Token::U8(6),
+                Token::TupleEnd,
+            ],
+        );
+        assert_de_tokens_error::<serde_test::Compact<u16_be>>(
+            &[Token::Tuple { len: 2 }, Token::U8(5), Token::TupleEnd],
+            "invalid length 1, expected 2 bytes",
+        );
+    }
+}
@@ -0,0 +1,204 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Conversion of whole slices between byte orders
+//!
+//! These are simple loops over the elements, with no data-dependent branches, so they compile
+//! to a vectorized byte shuffle, or to a plain copy when no conversion is needed.
+
+use super::{swap_bytes, ByteOrder, Endian, Endianness, Primitive};
+use core::{iter::FusedIterator, marker::PhantomData, mem, slice};
+
+/// Converts each value in `src` to the native byte order, storing the results in `dst`
+///
+/// # Panics
+///
+/// Panics if `src` and `dst` have different lengths.
+#[inline]
+pub fn convert_slice_to_native<T: Primitive, O: ByteOrder>(src: &[Endian<T, O>], dst: &mut [T]) {
+    assert_eq!(
+        src.len(),
+        dst.len(),
+        "source and destination have different lengths"
+    );
+    for (dst, src) in dst.iter_mut().zip(src) {
+        *dst = src.get();
+    }
+}
+
+/// Converts each value in `src` from the native byte order, storing the results in `dst`
+///
+/// # Panics
+///
+/// Panics if `src` and `dst` have different lengths.
+#[inline]
+pub fn convert_slice_from_native<T: Primitive, O: ByteOrder>(src: &[T], dst: &mut [Endian<T, O>]) {
+    assert_eq!(
+        src.len(),
+        dst.len(),
+        "source and destination have different lengths"
+    );
+    for (dst, src) in dst.iter_mut().zip(src) {
+        *dst = Endian::new(*src);
+    }
+}
+
+/// Reverses the byte order of each value in `values`
+///
+/// Floats are swapped as their bit patterns, so no byte-swapped float is ever loaded as a
+/// float.
+#[inline]
+pub fn swap_in_place<T: Primitive>(values: &mut [T]) {
+    let len = values.len();
+    // SAFETY: `T::Bits` has the same size and alignment as `T` and no invalid bit patterns.
+    let bits = unsafe { slice::from_raw_parts_mut(values.as_mut_ptr().cast::<T::Bits>(), len) };
+    for value in bits {
+        *value = swap_bytes(*value);
+    }
+}
+
+/// Converts each value in `values` to the native byte order in place
+///
+/// Returns the same memory, reinterpreted as native values.
+#[inline]
+pub fn to_native_in_place<T: Primitive, O: ByteOrder>(values: &mut [Endian<T, O>]) -> &mut [T] {
+    let len = values.len();
+    // SAFETY: `Endian<T, O>` is a transparent wrapper around `T::Bits`, which has the same
+    // size and alignment as `T` and no invalid bit patterns.
+    let bits = unsafe { slice::from_raw_parts_mut(values.as_mut_ptr().cast::<T::Bits>(), len) };
+    if !O::ENDIANNESS.is_native() {
+        swap_in_place(bits);
+    }
+    // SAFETY: See above. `Primitive` is only implemented for types without invalid bit
+    // patterns.
+    unsafe { slice::from_raw_parts_mut(bits.as_mut_ptr().cast::<T>(), len) }
+}
+
+/// Converts each value in `values` from the native byte order in place
+///
+/// Returns the same memory, reinterpreted as values stored in `O` byte order.
+#[inline]
+pub fn from_native_in_place<T: Primitive, O: ByteOrder>(values: &mut [T]) -> &mut [Endian<T, O>] {
+    let len = values.len();
+    // SAFETY: `T::Bits` has the same size and alignment as `T` and no invalid bit patterns.
+    let bits = unsafe { slice::from_raw_parts_mut(values.as_mut_ptr().cast::<T::Bits>(), len) };
+    if !O::ENDIANNESS.is_native() {
+        swap_in_place(bits);
+    }
+    // SAFETY: `Endian<T, O>` is a transparent wrapper around `T::Bits`.
+    unsafe { slice::from_raw_parts_mut(bits.as_mut_ptr().cast::<Endian<T, O>>(), len) }
+}
+
+/// Returns an iterator over the `T`s stored in `endianness` byte order in `bytes`
+///
+/// `bytes` need not be aligned. Any trailing bytes which do not make up a whole `T` are
+/// available from [`ReadIter::remainder()`].
+#[inline]
+pub fn read_iter<T: Primitive>(bytes: &[u8], endianness: Endianness) -> ReadIter<'_, T> {
+    ReadIter {
+        chunks: bytes.chunks_exact(mem::size_of::<T>()),
+        endianness,
+        marker: PhantomData,
+    }
+}
+
+/// An iterator over the values stored in a byte slice
+///
+/// This is created by [`read_iter()`].
+#[derive(Clone, Debug)]
+pub struct ReadIter<'a, T> {
+    chunks: slice::ChunksExact<'a, u8>,
+    endianness: Endianness,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T: Primitive> ReadIter<'a, T> {
+    /// Returns the trailing bytes which do not make up a whole `T`
+    #[inline]
+    pub fn remainder(&self) -> &'a [u8] {
+        self.chunks.remainder()
+    }
+
+    #[inline(always)]
+    fn decode(&self, chunk: &[u8]) -> T {
+        // SAFETY: `chunks_exact()` only yields chunks of `size_of::<T>()` bytes, and `T::Bits`
+        // has no invalid bit patterns.
+        let bits = unsafe { chunk.as_ptr().cast::<T::Bits>().read_unaligned() };
+        self.endianness.decode(bits)
+    }
+}
+
+impl<T: Primitive> Iterator for ReadIter<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        let chunk = self.chunks.next()?;
+        Some(self.decode(chunk))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<T> {
+        let chunk = self.chunks.nth(n)?;
+        Some(self.decode(chunk))
+    }
+}
+
+impl<T: Primitive> DoubleEndedIterator for ReadIter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        let chunk = self.chunks.next_back()?;
+        Some(self.decode(chunk))
+    }
+}
+
+impl<T: Primitive> ExactSizeIterator for ReadIter<'_, T> {}
+
+impl<T: Primitive> FusedIterator for ReadIter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swap_floats_bit_exact() {
+        let bits = [0x7fa0_0001_u32, 0xff80_0000, 0x3f80_0000];
+        let mut values = bits.map(f32::from_bits);
+        swap_in_place(&mut values);
+        assert_eq!(values.map(f32::to_bits), bits.map(u32::swap_bytes));
+        swap_in_place(&mut values);
+        assert_eq!(values.map(f32::to_bits), bits);
+    }
+}
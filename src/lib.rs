@@ -44,9 +44,12 @@
     clippy::unreadable_literal,
     clippy::wildcard_imports
 )]
-
 #![cfg_attr(not(test), no_std)]
 
+#[cfg(feature = "bitfield")]
+pub mod bitfield;
+#[cfg(feature = "bytes")]
+pub mod bytes;
 #[cfg(feature = "endian")]
 pub mod endian;
 #[cfg(feature = "volatile")]
@@ -70,7 +73,7 @@ macro_rules! extern_sym {
     }};
     (mut $sym:ident as $t:ty) => {{
         #[allow(improper_ctypes)]
-        extern "C" { static $sym: $t; }
+        extern "C" { static mut $sym: $t; }
         // SAFETY: The value is not accessed, we only take its address.
         // The `addr_of_mut!()` macro ensures that no intermediate reference is created.
         unsafe {
@@ -78,3 +81,85 @@ macro_rules! extern_sym {
         }
     }};
 }
+
+/// Returns the range between a pair of linker symbols, as a `Range<*const T>`, or a
+/// `Range<*mut T>` if prefixed with `mut`
+///
+/// This is intended for regions defined by the linker script, such as
+/// `__bss_start..__bss_end`. In debug builds, the symbols are checked to be in order, aligned
+/// for `T`, and a whole number of `T`s apart.
+///
+/// ```no_run
+/// let bss = libsa::extern_range!(mut __bss_start..__bss_end as u8);
+/// let ctors = libsa::extern_range!(__init_array_start..__init_array_end as extern "C" fn());
+/// ```
+#[macro_export]
+macro_rules! extern_range {
+    ($start:ident .. $end:ident) => { $crate::extern_range!($start..$end as u8) };
+    (mut $start:ident .. $end:ident) => { $crate::extern_range!(mut $start..$end as u8) };
+    ($start:ident .. $end:ident as $t:ty) => {{
+        let start = $crate::extern_sym!($start as $t);
+        let end = $crate::extern_sym!($end as $t);
+        $crate::check_extern_range(start, end);
+        start..end
+    }};
+    (mut $start:ident .. $end:ident as $t:ty) => {{
+        let start = $crate::extern_sym!(mut $start as $t);
+        let end = $crate::extern_sym!(mut $end as $t);
+        $crate::check_extern_range(start, end);
+        start..end
+    }};
+}
+
+/// Returns the region between a pair of linker symbols as a `&'static [T]`
+///
+/// This expands to a call to an unsafe function, so it must be used in an `unsafe` block.
+/// The caller must ensure that the region is initialized with valid values of `T`, and that
+/// it is not mutated for the rest of the program.
+///
+/// See [`extern_range!`] for the checks made in debug builds.
+///
+/// ```no_run
+/// let ctors = unsafe {
+///     libsa::extern_slice!(__init_array_start..__init_array_end as extern "C" fn())
+/// };
+/// for ctor in ctors {
+///     ctor();
+/// }
+/// ```
+#[macro_export]
+macro_rules! extern_slice {
+    ($start:ident .. $end:ident as $t:ty) => {
+        $crate::slice_from_extern_range($crate::extern_range!($start..$end as $t))
+    };
+}
+
+/// Checks that a range between linker symbols describes a valid array of `T`s
+#[doc(hidden)]
+#[inline]
+pub fn check_extern_range<T>(start: *const T, end: *const T) {
+    debug_assert!(
+        core::mem::size_of::<T>() != 0,
+        "cannot form a range of zero-sized types"
+    );
+    debug_assert!(start <= end, "start symbol is after end symbol");
+    debug_assert!(start.is_aligned(), "start symbol is not aligned");
+    debug_assert!(
+        (end as usize - start as usize).is_multiple_of(core::mem::size_of::<T>()),
+        "range is not a whole number of elements"
+    );
+}
+
+/// Creates a slice from a range between linker symbols
+///
+/// # Safety
+///
+/// The range must describe an initialized array of `T`, which is not mutated for the rest of
+/// the program.
+#[doc(hidden)]
+#[inline]
+pub unsafe fn slice_from_extern_range<T>(range: core::ops::Range<*const T>) -> &'static [T] {
+    let len = (range.end as usize - range.start as usize) / core::mem::size_of::<T>();
+    // SAFETY: The caller guarantees the range is a valid array of `T`.
+    unsafe { core::slice::from_raw_parts(range.start, len) }
+}
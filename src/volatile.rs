@@ -28,92 +28,232 @@
  * SPDX-License-Identifier: BSD-3-Clause
  */
 
-use core::{cell::UnsafeCell, fmt};
+//! Volatile memory access
+//!
+//! [`Volatile<T>`] owns a value and may be embedded in structures, while [`VolatilePtr`]
+//! accesses a value through a raw pointer and is suitable for memory-mapped I/O.
+//!
+//! Both take an [`access`] marker restricting them to reads or writes, with the aliases
+//! [`ReadOnly<T>`], [`WriteOnly<T>`] and [`ReadWrite<T>`] for the common case.
+//!
+//! Arrays of registers are accessed one element at a time with [`VolatileArray`] and
+//! [`VolatileSlice`].
+//!
+//! Registers of devices whose byte order differs from the CPU's are declared with
+//! [`VolatileLe<T>`] and [`VolatileBe<T>`], which convert to and from the native byte order.
+//!
+//! 64-bit values which are split across two 32-bit registers are accessed with
+//! [`VolatileSplitU64`] and [`VolatileSplitPtr`].
+//!
+//! Whole devices are described with [`register_block!`](crate::register_block!), which
+//! transcribes a datasheet's register table into a type with an accessor for each register.
+//!
+//! Drivers may be written against the [`RegisterRead`] and [`RegisterWrite`] traits instead of
+//! a concrete type, so that they can be tested on the host with the `mock` feature.
+//!
+//! Device accesses are ordered with respect to normal memory with the [`barrier`] functions,
+//! or with the `read_acquire()` and `write_release()` methods.
+//!
+//! On x86, I/O ports are accessed with [`Port`], which implements the same traits.
+
+use self::access::{Access, Readable, Writable};
+use core::{cell::UnsafeCell, fmt, marker::PhantomData, ops, ptr::NonNull};
+
+pub mod access;
+pub mod barrier;
+mod block;
+mod endian;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod port;
+mod ptr;
+mod register;
+mod slice;
+mod split;
+
+#[doc(hidden)]
+pub use self::block::{check_layout, layout_align, layout_end};
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub use self::port::{Port, PortReadOnly, PortValue, PortWriteOnly};
+pub use self::{
+    endian::{VolatileBe, VolatileEndian, VolatileLe},
+    ptr::VolatilePtr,
+    register::{Register, RegisterRead, RegisterWrite},
+    slice::{Iter, VolatileArray, VolatileSlice},
+    split::{AddressOutOfRange, SplitOrder, VolatileSplitPtr, VolatileSplitU64},
+};
+
+/// An error returned when polling a value did not succeed within the limit
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Timeout;
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("timed out waiting for value")
+    }
+}
 
 /// A wrapper type providing volatile access to a value
+///
+/// The access marker `A` determines whether the value may be read, written, or both.
 #[repr(transparent)]
-pub struct Volatile<T> {
+pub struct Volatile<T, A = access::ReadWrite> {
     value: UnsafeCell<T>,
+    access: PhantomData<A>,
 }
 
+/// A value which may only be read, such as a status register
+pub type ReadOnly<T> = Volatile<T, access::ReadOnly>;
+
+/// A value which may only be written, such as a doorbell register
+pub type WriteOnly<T> = Volatile<T, access::WriteOnly>;
+
+/// A value which may be read and written
+pub type ReadWrite<T> = Volatile<T, access::ReadWrite>;
+
 impl<T> Volatile<T> {
     #[inline]
     pub const fn new(value: T) -> Volatile<T> {
+        Self::with_access(value)
+    }
+}
+
+impl<T, A: Access> Volatile<T, A> {
+    /// Creates a value with the access marker `A`, e.g. `ReadOnly::with_access(0)`
+    #[inline]
+    pub const fn with_access(value: T) -> Volatile<T, A> {
         Self {
             value: UnsafeCell::new(value),
+            access: PhantomData,
         }
     }
 
     #[inline]
-    pub fn read(&self) -> T {
+    pub fn read(&self) -> T
+    where
+        A: Readable,
+    {
         // SAFETY: `Volatile` owns the data it stores.
         unsafe { core::ptr::read_volatile(self.value.get()) }
     }
 
     #[inline]
-    pub fn write(&self, value: T) {
+    pub fn write(&self, value: T)
+    where
+        A: Writable,
+    {
         // SAFETY: `Volatile` owns the data it stores.
         unsafe { core::ptr::write_volatile(self.value.get(), value) };
     }
-}
 
-impl<T: fmt::Debug> fmt::Debug for Volatile<T> {
+    /// Reads the value, ordering it before any subsequent memory accesses
+    ///
+    /// See [`barrier::mmio_rmb()`].
     #[inline]
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Debug::fmt(&self.read(), f)
+    pub fn read_acquire(&self) -> T
+    where
+        A: Readable,
+    {
+        let value = self.read();
+        barrier::mmio_rmb();
+        value
     }
-}
 
-impl<T: Default> Default for Volatile<T> {
-    fn default() -> Self {
-        Self::new(T::default())
+    /// Writes the value, ordering it after any prior memory writes
+    ///
+    /// See [`barrier::mmio_wmb()`].
+    #[inline]
+    pub fn write_release(&self, value: T)
+    where
+        A: Writable,
+    {
+        barrier::mmio_wmb();
+        self.write(value);
     }
-}
 
-/// Represents a 64 bit pointer, of which its low and high bits are split in 32-bit-aligned
-/// volatile memory.
-// TODO: endian memes
-#[repr(C)]
-#[derive(Default)]
-#[cfg(target_pointer_width = "64")]
-#[allow(clippy::module_name_repetitions)]
-pub struct VolatileSplitPtr<T> {
-    low: Volatile<u32>,
-    high: Volatile<u32>,
-    marker: core::marker::PhantomData<T>,
-}
+    /// Returns a [`VolatilePtr`] to the value
+    #[inline]
+    pub fn as_volatile_ptr(&self) -> VolatilePtr<'_, T, A> {
+        // SAFETY: `UnsafeCell::get()` never returns null, and the pointer is valid for as long
+        // as `self` is borrowed.
+        unsafe { VolatilePtr::with_access(NonNull::new_unchecked(self.value.get())) }
+    }
 
-impl<T> fmt::Debug for VolatileSplitPtr<T> {
+    /// Reads the value, and writes back the result of `f`
+    ///
+    /// This is not atomic; the value may change between the read and the write.
     #[inline]
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Debug::fmt(&self.get(), f)
+    pub fn update(&self, f: impl FnOnce(T) -> T)
+    where
+        T: Copy,
+        A: Readable + Writable,
+    {
+        self.as_volatile_ptr().update(f);
     }
-}
 
-impl<T> VolatileSplitPtr<T> {
-    /// Gets the contained pointer
+    /// Sets the bits which are set in `bits`
     #[inline]
-    pub fn get(&self) -> *mut T {
-        let low = self.low.read();
-        let high = self.high.read();
+    pub fn set_bits(&self, bits: T)
+    where
+        T: Copy + ops::BitOr<Output = T>,
+        A: Readable + Writable,
+    {
+        self.as_volatile_ptr().set_bits(bits);
+    }
 
-        (((high as u64) << 32) | (low as u64)) as *mut T
+    /// Clears the bits which are set in `bits`
+    #[inline]
+    pub fn clear_bits(&self, bits: T)
+    where
+        T: Copy + ops::BitAnd<Output = T> + ops::Not<Output = T>,
+        A: Readable + Writable,
+    {
+        self.as_volatile_ptr().clear_bits(bits);
     }
 
-    /// Sets the contained pointer
+    /// Replaces the bits which are set in `mask` with those of `value`
     #[inline]
-    pub fn set(&self, ptr: *mut T) {
-        #![allow(clippy::cast_possible_truncation)]
-        let ptr = ptr as usize;
+    pub fn modify(&self, mask: T, value: T)
+    where
+        T: Copy + ops::BitAnd<Output = T> + ops::BitOr<Output = T> + ops::Not<Output = T>,
+        A: Readable + Writable,
+    {
+        self.as_volatile_ptr().modify(mask, value);
+    }
 
-        self.low.write(ptr as u32);
-        self.high.write((ptr >> 32) as u32);
+    /// Reads the value until it satisfies `pred`, returning the value which did
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Timeout`] if the value does not satisfy `pred` after `spin_limit` reads.
+    #[inline]
+    pub fn wait_until(&self, pred: impl FnMut(T) -> bool, spin_limit: usize) -> Result<T, Timeout>
+    where
+        T: Copy,
+        A: Readable,
+    {
+        self.as_volatile_ptr().wait_until(pred, spin_limit)
     }
+}
 
-    /// Sets the component parts of the contained pointer
+impl<T: fmt::Debug, A: Readable> fmt::Debug for Volatile<T, A> {
     #[inline]
-    pub fn set_parts(&self, high: u32, low: u32) {
-        self.low.write(low);
-        self.high.write(high);
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.read(), f)
+    }
+}
+
+/// The value is not printed, as it cannot be read.
+impl<T> fmt::Debug for Volatile<T, access::WriteOnly> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<write-only>")
+    }
+}
+
+impl<T: Default, A: Access> Default for Volatile<T, A> {
+    fn default() -> Self {
+        Self::with_access(T::default())
     }
 }
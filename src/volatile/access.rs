@@ -0,0 +1,92 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Access permissions for volatile values
+//!
+//! Many device registers may only be read or only be written, and on some devices reading a
+//! write-only register has side effects. The access marker of a [`Volatile`](super::Volatile)
+//! or [`VolatilePtr`](super::VolatilePtr) determines which operations are available, so that
+//! such mistakes are compile errors.
+//!
+//! ```compile_fail
+//! use libsa::volatile::ReadOnly;
+//!
+//! let status = ReadOnly::with_access(0_u32);
+//! status.write(1);
+//! ```
+//!
+//! ```compile_fail
+//! use libsa::volatile::WriteOnly;
+//!
+//! let doorbell = WriteOnly::with_access(0_u32);
+//! doorbell.read();
+//! ```
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// An access permission
+///
+/// This trait is sealed and implemented only by [`ReadOnly`], [`WriteOnly`] and
+/// [`ReadWrite`].
+pub trait Access: Copy + Default + private::Sealed + 'static {}
+
+/// An access permission which allows reads
+pub trait Readable: Access {}
+
+/// An access permission which allows writes
+pub trait Writable: Access {}
+
+/// The value may only be read
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ReadOnly;
+
+/// The value may only be written
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct WriteOnly;
+
+/// The value may be read and written
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ReadWrite;
+
+impl private::Sealed for ReadOnly {}
+impl private::Sealed for WriteOnly {}
+impl private::Sealed for ReadWrite {}
+
+impl Access for ReadOnly {}
+impl Access for WriteOnly {}
+impl Access for ReadWrite {}
+
+impl Readable for ReadOnly {}
+impl Readable for ReadWrite {}
+
+impl Writable for WriteOnly {}
+impl Writable for ReadWrite {}
@@ -0,0 +1,176 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Memory barriers for ordering device accesses
+//!
+//! Volatile accesses are not reordered with respect to each other by the compiler, but may be
+//! reordered with respect to normal memory accesses, and the CPU may reorder either. These
+//! barriers order them, e.g. between writing a DMA descriptor and ringing the doorbell which
+//! tells the device to read it.
+//!
+//! The `dma_*` barriers order accesses to normal memory shared with a device, and are cheaper
+//! than the `mmio_*` barriers, which also order accesses to device memory.
+//!
+//! On 32-bit x86 without SSE2, the fence instructions are unavailable and a locked instruction
+//! is used instead. On architectures other than x86, ARM64 and RISC-V, the barriers are only
+//! sequentially consistent fences, which are not guaranteed to order accesses to devices.
+
+use core::sync::atomic::{self, Ordering};
+
+macro_rules! barrier {
+    ($(#[$meta:meta])* $name:ident {
+        x86: $x86:tt,
+        aarch64: $aarch64:literal,
+        riscv: $riscv:literal $(,)?
+    }) => {
+        $(#[$meta])*
+        #[inline(always)]
+        pub fn $name() {
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            barrier!(@x86 $x86);
+            #[cfg(target_arch = "aarch64")]
+            // SAFETY: Barriers have no effects other than ordering memory accesses.
+            unsafe {
+                core::arch::asm!($aarch64, options(nostack, preserves_flags));
+            }
+            #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+            // SAFETY: Barriers have no effects other than ordering memory accesses.
+            unsafe {
+                core::arch::asm!($riscv, options(nostack, preserves_flags));
+            }
+            #[cfg(not(any(
+                target_arch = "x86",
+                target_arch = "x86_64",
+                target_arch = "aarch64",
+                target_arch = "riscv32",
+                target_arch = "riscv64",
+            )))]
+            atomic::fence(Ordering::SeqCst);
+        }
+    };
+
+    // x86 does not reorder stores with other stores, or loads with other loads, to normal
+    // memory, so only the compiler needs to be prevented from reordering them.
+    (@x86 compiler) => { atomic::compiler_fence(Ordering::SeqCst) };
+    (@x86 $insn:literal) => {
+        #[cfg(any(target_arch = "x86_64", target_feature = "sse2"))]
+        // SAFETY: Barriers have no effects other than ordering memory accesses.
+        unsafe {
+            core::arch::asm!($insn, options(nostack, preserves_flags));
+        }
+        // The fence instructions require SSE or SSE2, but a locked instruction is a full
+        // barrier. OR-ing zero into the top of the stack leaves it unchanged.
+        #[cfg(all(target_arch = "x86", not(target_feature = "sse2")))]
+        // SAFETY: See above.
+        unsafe {
+            core::arch::asm!("lock or dword ptr [esp], 0", options(nostack));
+        }
+    };
+}
+
+barrier! {
+    /// Orders prior writes to DMA memory before subsequent writes to DMA memory
+    dma_wmb {
+        x86: compiler,
+        aarch64: "dmb oshst",
+        riscv: "fence w, w",
+    }
+}
+
+barrier! {
+    /// Orders prior reads from DMA memory before subsequent reads from DMA memory
+    dma_rmb {
+        x86: compiler,
+        aarch64: "dmb oshld",
+        riscv: "fence r, r",
+    }
+}
+
+barrier! {
+    /// Orders prior accesses to DMA memory before subsequent accesses to DMA memory
+    dma_mb {
+        x86: "mfence",
+        aarch64: "dmb osh",
+        riscv: "fence rw, rw",
+    }
+}
+
+barrier! {
+    /// Orders prior writes to memory or devices before subsequent writes to devices
+    ///
+    /// This is needed between filling a descriptor and writing the register which tells the
+    /// device to read it.
+    mmio_wmb {
+        x86: "sfence",
+        aarch64: "dsb st",
+        riscv: "fence ow, ow",
+    }
+}
+
+barrier! {
+    /// Orders prior reads from devices before subsequent reads from memory or devices
+    ///
+    /// This is needed between reading a status register and reading the memory it says the
+    /// device has written.
+    mmio_rmb {
+        x86: "lfence",
+        aarch64: "dsb ld",
+        riscv: "fence ir, ir",
+    }
+}
+
+barrier! {
+    /// Orders all prior accesses to memory or devices before all subsequent accesses
+    io_fence {
+        x86: "mfence",
+        aarch64: "dsb sy",
+        riscv: "fence iorw, iorw",
+    }
+}
+
+/// Prevents the compiler from reordering memory accesses across this point
+///
+/// This does not emit any instructions, so the CPU may still reorder accesses.
+#[inline(always)]
+pub fn compiler_barrier() {
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
+/// Prevents the compiler from moving memory accesses after this point before it
+#[inline(always)]
+pub fn compiler_acquire() {
+    atomic::compiler_fence(Ordering::Acquire);
+}
+
+/// Prevents the compiler from moving memory accesses before this point after it
+#[inline(always)]
+pub fn compiler_release() {
+    atomic::compiler_fence(Ordering::Release);
+}
@@ -0,0 +1,217 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Declaration of memory-mapped register blocks
+
+/// Checks the layout of a register block, given the offset, size and alignment of each
+/// register, in the order they were declared
+///
+/// This is evaluated at compile time by [`register_block!`](crate::register_block!).
+#[doc(hidden)]
+pub const fn check_layout(registers: &[(usize, usize, usize)], size: usize) {
+    let mut end = 0;
+    let mut i = 0;
+    while i < registers.len() {
+        let (offset, len, align) = registers[i];
+        assert!(offset % align == 0, "register is not suitably aligned");
+        assert!(
+            offset >= end,
+            "registers overlap or are not in order of offset"
+        );
+        end = offset + len;
+        i += 1;
+    }
+    assert!(end <= size, "registers extend past the end of the block");
+}
+
+/// Returns the end of the last register of a register block
+#[doc(hidden)]
+pub const fn layout_end(registers: &[(usize, usize, usize)]) -> usize {
+    match registers.last() {
+        Some(&(offset, len, _)) => offset + len,
+        None => 0,
+    }
+}
+
+/// Returns the greatest alignment of the registers of a register block
+#[doc(hidden)]
+pub const fn layout_align(registers: &[(usize, usize, usize)]) -> usize {
+    let mut max = 1;
+    let mut i = 0;
+    while i < registers.len() {
+        if registers[i].2 > max {
+            max = registers[i].2;
+        }
+        i += 1;
+    }
+    max
+}
+
+/// Declares a block of memory-mapped registers
+///
+/// Each register gives its offset, name, type and access, as listed in the device's datasheet,
+/// in order of offset. The resulting type holds the base address of the block, and has a
+/// method for each register returning a [`VolatilePtr`](crate::volatile::VolatilePtr) with
+/// the given [`access`](crate::volatile::access). The size of the block may be given in
+/// brackets after its name; otherwise it ends with the last register.
+///
+/// It is a compile error for registers to overlap, to be out of order, to be misaligned, or to
+/// extend past the end of the block, so gaps in the datasheet need no padding.
+///
+/// ```no_run
+/// use core::ptr::NonNull;
+///
+/// libsa::register_block! {
+///     /// ARM PrimeCell UART (PL011)
+///     pub struct Pl011[0x1000] {
+///         0x000 => pub dr: u32 [ReadWrite];
+///         0x004 => pub rsr: u32 [ReadWrite];
+///         0x018 => pub fr: u32 [ReadOnly];
+///         0x024 => pub ibrd: u32 [ReadWrite];
+///         0x028 => pub fbrd: u32 [ReadWrite];
+///         0x02c => pub lcr_h: u32 [ReadWrite];
+///         0x030 => pub cr: u32 [ReadWrite];
+///         0x044 => pub icr: u32 [WriteOnly];
+///     }
+/// }
+///
+/// const TXFF: u32 = 1 << 5;
+///
+/// let base = NonNull::new(0x0900_0000 as *mut u8).unwrap();
+/// let uart = unsafe { Pl011::new(base) };
+/// while uart.fr().read() & TXFF != 0 {}
+/// uart.dr().write(b'A' as u32);
+/// ```
+///
+/// Overlapping registers are rejected:
+///
+/// ```compile_fail
+/// libsa::register_block! {
+///     struct Overlap {
+///         0x0 => data: u64 [ReadWrite];
+///         0x4 => status: u32 [ReadOnly];
+///     }
+/// }
+/// ```
+///
+/// As are misaligned registers:
+///
+/// ```compile_fail
+/// libsa::register_block! {
+///     struct Misaligned {
+///         0x0 => data: u8 [ReadWrite];
+///         0x2 => status: u32 [ReadOnly];
+///     }
+/// }
+/// ```
+///
+/// And registers past the end of the block:
+///
+/// ```compile_fail
+/// libsa::register_block! {
+///     struct Overflow[0x8] {
+///         0x0 => data: u32 [ReadWrite];
+///         0x8 => status: u32 [ReadOnly];
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! register_block {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident $([$size:expr])? {
+            $(
+                $(#[$reg_meta:meta])*
+                $offset:literal => $reg_vis:vis $reg:ident: $ty:ty [$access:ident];
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug)]
+        $vis struct $name<'a> {
+            base: ::core::ptr::NonNull<u8>,
+            marker: ::core::marker::PhantomData<&'a ()>,
+        }
+
+        const _: () = $crate::volatile::check_layout($name::REGISTERS, $name::SIZE);
+
+        impl<'a> $name<'a> {
+            const REGISTERS: &'static [(usize, usize, usize)] = &[$((
+                $offset,
+                ::core::mem::size_of::<$ty>(),
+                ::core::mem::align_of::<$ty>(),
+            )),*];
+
+            /// The size of the register block, in bytes
+            pub const SIZE: usize = $crate::register_block!(@size Self::REGISTERS $(, $size)?);
+
+            /// The alignment required of the base address
+            pub const ALIGN: usize = $crate::volatile::layout_align(Self::REGISTERS);
+
+            /// Creates a register block at `base`
+            ///
+            /// # Safety
+            ///
+            /// `base` must point to the registers of the device, be valid for volatile reads
+            /// and writes of `SIZE` bytes for the lifetime `'a`, and be aligned to `ALIGN`.
+            #[inline(always)]
+            pub const unsafe fn new(base: ::core::ptr::NonNull<u8>) -> Self {
+                Self {
+                    base,
+                    marker: ::core::marker::PhantomData,
+                }
+            }
+
+            /// Returns the base address of the register block
+            #[inline(always)]
+            pub const fn as_ptr(self) -> ::core::ptr::NonNull<u8> {
+                self.base
+            }
+
+            $(
+                $(#[$reg_meta])*
+                #[inline(always)]
+                $reg_vis fn $reg(
+                    self,
+                ) -> $crate::volatile::VolatilePtr<'a, $ty, $crate::volatile::access::$access> {
+                    // SAFETY: The layout was checked at compile time, so the register lies
+                    // within the block and is aligned.
+                    unsafe {
+                        $crate::volatile::VolatilePtr::new(self.base.add($offset).cast::<$ty>())
+                            .restrict()
+                    }
+                }
+            )*
+        }
+    };
+
+    (@size $registers:expr) => { $crate::volatile::layout_end($registers) };
+    (@size $registers:expr, $size:expr) => { $size };
+}
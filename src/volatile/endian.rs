@@ -0,0 +1,256 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Volatile values stored in a fixed byte order
+
+use super::{
+    access::{self, Access, Readable, Writable},
+    Timeout, Volatile, VolatilePtr,
+};
+use crate::endian::{BigEndian, ByteOrder, Endian, LittleEndian, Primitive};
+use core::{fmt, ops};
+
+/// A volatile `T` stored in `O` byte order
+///
+/// This is a [`Volatile<Endian<T, O>>`](Volatile) which converts to and from the native byte
+/// order on each access, for devices whose byte order differs from the CPU's.
+#[repr(transparent)]
+pub struct VolatileEndian<T: Primitive, O, A = access::ReadWrite> {
+    inner: Volatile<Endian<T, O>, A>,
+}
+
+/// A volatile little-endian value
+pub type VolatileLe<T, A = access::ReadWrite> = VolatileEndian<T, LittleEndian, A>;
+
+/// A volatile big-endian value
+pub type VolatileBe<T, A = access::ReadWrite> = VolatileEndian<T, BigEndian, A>;
+
+impl<T: Primitive, O: ByteOrder> VolatileEndian<T, O> {
+    #[inline]
+    pub const fn new(value: T) -> VolatileEndian<T, O> {
+        Self::with_access(value)
+    }
+}
+
+impl<T: Primitive, O: ByteOrder, A: Access> VolatileEndian<T, O, A> {
+    /// Creates a value with the access marker `A`
+    #[inline]
+    pub const fn with_access(value: T) -> VolatileEndian<T, O, A> {
+        Self {
+            inner: Volatile::with_access(Endian::new(value)),
+        }
+    }
+
+    #[inline]
+    pub fn read(&self) -> T
+    where
+        A: Readable,
+    {
+        self.inner.read().get()
+    }
+
+    #[inline]
+    pub fn write(&self, value: T)
+    where
+        A: Writable,
+    {
+        self.inner.write(Endian::new(value));
+    }
+
+    /// Reads the value, ordering it before any subsequent memory accesses
+    ///
+    /// See [`barrier::mmio_rmb()`](super::barrier::mmio_rmb).
+    #[inline]
+    pub fn read_acquire(&self) -> T
+    where
+        A: Readable,
+    {
+        self.inner.read_acquire().get()
+    }
+
+    /// Writes the value, ordering it after any prior memory writes
+    ///
+    /// See [`barrier::mmio_wmb()`](super::barrier::mmio_wmb).
+    #[inline]
+    pub fn write_release(&self, value: T)
+    where
+        A: Writable,
+    {
+        self.inner.write_release(Endian::new(value));
+    }
+
+    /// Returns a [`VolatilePtr`] to the value, without conversion
+    #[inline]
+    pub fn as_volatile_ptr(&self) -> VolatilePtr<'_, Endian<T, O>, A> {
+        self.inner.as_volatile_ptr()
+    }
+
+    /// Reads the value, and writes back the result of `f`
+    ///
+    /// This is not atomic; the value may change between the read and the write.
+    #[inline]
+    pub fn update(&self, f: impl FnOnce(T) -> T)
+    where
+        A: Readable + Writable,
+    {
+        self.inner.update(|value| Endian::new(f(value.get())));
+    }
+
+    /// Sets the bits which are set in `bits`
+    ///
+    /// The mask is converted to `O` byte order once, rather than converting the register value.
+    #[inline]
+    pub fn set_bits(&self, bits: T)
+    where
+        T: ops::BitOr<Output = T>,
+        A: Readable + Writable,
+    {
+        self.inner.set_bits(Endian::new(bits));
+    }
+
+    /// Clears the bits which are set in `bits`
+    ///
+    /// The mask is converted to `O` byte order once, rather than converting the register value.
+    #[inline]
+    pub fn clear_bits(&self, bits: T)
+    where
+        T: ops::BitAnd<Output = T> + ops::Not<Output = T>,
+        A: Readable + Writable,
+    {
+        self.inner.clear_bits(Endian::new(bits));
+    }
+
+    /// Replaces the bits which are set in `mask` with those of `value`
+    #[inline]
+    pub fn modify(&self, mask: T, value: T)
+    where
+        T: ops::BitAnd<Output = T> + ops::BitOr<Output = T> + ops::Not<Output = T>,
+        A: Readable + Writable,
+    {
+        self.inner.modify(Endian::new(mask), Endian::new(value));
+    }
+
+    /// Reads the value until it satisfies `pred`, returning the value which did
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Timeout`] if the value does not satisfy `pred` after `spin_limit` reads.
+    #[inline]
+    pub fn wait_until(
+        &self,
+        mut pred: impl FnMut(T) -> bool,
+        spin_limit: usize,
+    ) -> Result<T, Timeout>
+    where
+        A: Readable,
+    {
+        self.inner
+            .wait_until(|value| pred(value.get()), spin_limit)
+            .map(Endian::get)
+    }
+}
+
+impl<T: Primitive + fmt::Debug, O: ByteOrder, A: Readable> fmt::Debug for VolatileEndian<T, O, A> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.read(), f)
+    }
+}
+
+/// The value is not printed, as it cannot be read.
+impl<T: Primitive, O: ByteOrder> fmt::Debug for VolatileEndian<T, O, access::WriteOnly> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<write-only>")
+    }
+}
+
+impl<T: Primitive + Default, O: ByteOrder, A: Access> Default for VolatileEndian<T, O, A> {
+    fn default() -> Self {
+        Self::with_access(T::default())
+    }
+}
+
+impl<T: Primitive, O: ByteOrder, A: Access> VolatilePtr<'_, Endian<T, O>, A> {
+    /// Reads the value, converting it to the native byte order
+    #[inline]
+    pub fn read_native(self) -> T
+    where
+        A: Readable,
+    {
+        self.read().get()
+    }
+
+    /// Writes the value, converting it from the native byte order
+    #[inline]
+    pub fn write_native(self, value: T)
+    where
+        A: Writable,
+    {
+        self.write(Endian::new(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits_in_foreign_order() {
+        let reg = VolatileBe::<u32>::new(0x0000_0001);
+        reg.set_bits(1 << 31);
+        assert_eq!(reg.read(), 0x8000_0001);
+        assert_eq!(
+            reg.as_volatile_ptr().read().to_raw().to_ne_bytes(),
+            [0x80, 0, 0, 1]
+        );
+        reg.clear_bits(1);
+        assert_eq!(reg.read(), 0x8000_0000);
+        assert_eq!(
+            reg.as_volatile_ptr().read().to_raw().to_ne_bytes(),
+            [0x80, 0, 0, 0]
+        );
+        reg.modify(0xff00_ff00, 0x1234_5678);
+        assert_eq!(reg.read(), 0x1200_5600);
+        assert_eq!(reg.as_volatile_ptr().read_native(), 0x1200_5600);
+    }
+
+    #[test]
+    fn update_and_wait() {
+        let reg = VolatileLe::<u16>::new(0x00ff);
+        reg.update(|value| value << 4);
+        assert_eq!(
+            reg.as_volatile_ptr().read().to_raw().to_ne_bytes(),
+            [0xf0, 0x0f]
+        );
+        assert_eq!(reg.wait_until(|value| value == 0x0ff0, 1), Ok(0x0ff0));
+        assert_eq!(reg.wait_until(|value| value == 0, 3), Err(Timeout));
+    }
+}
@@ -0,0 +1,366 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Mock registers for testing drivers on the host
+//!
+//! A [`Mock`] stands in for a device's register space. Each [`MockRegister`] handed out by it
+//! implements [`RegisterRead`] and [`RegisterWrite`], and every access is recorded. Tests can
+//! script the values returned by reads, and assert the exact sequence of accesses.
+//!
+//! ```
+//! use libsa::volatile::{mock::Mock, RegisterRead, RegisterWrite};
+//!
+//! fn uart_putc(status: &impl RegisterRead<u8>, data: &impl RegisterWrite<u8>, c: u8) {
+//!     while status.read() & 0x20 == 0 {}
+//!     data.write(c);
+//! }
+//!
+//! let mock = Mock::new();
+//! let status = mock.register::<u8>(0x05);
+//! let data = mock.register::<u8>(0x00);
+//!
+//! mock.expect_read(0x05, 0x00);
+//! mock.expect_read(0x05, 0x20);
+//! mock.expect_write(0x00, b'A' as u64);
+//!
+//! uart_putc(&status, &data, b'A');
+//! mock.assert_done();
+//! ```
+
+use super::{
+    access::{self, Readable, Writable},
+    RegisterRead, RegisterWrite,
+};
+use alloc::{collections::BTreeMap, collections::VecDeque, vec::Vec};
+use core::{cell::RefCell, marker::PhantomData};
+
+/// An access to a mock register
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MockAccess {
+    Read { addr: usize, value: u64 },
+    Write { addr: usize, value: u64 },
+}
+
+/// A value which can be stored in a mock register
+pub trait MockValue: Copy {
+    fn to_u64(self) -> u64;
+
+    /// Creates a value from the low bits of `value`
+    fn from_u64(value: u64) -> Self;
+}
+
+macro_rules! mock_values {
+    ($($type:ty),*) => {$(
+        impl MockValue for $type {
+            #[inline]
+            fn to_u64(self) -> u64 {
+                self as u64
+            }
+
+            #[inline]
+            #[allow(clippy::cast_possible_truncation)]
+            fn from_u64(value: u64) -> Self {
+                value as $type
+            }
+        }
+    )*};
+}
+
+mock_values!(u8, u16, u32, u64, usize);
+
+#[derive(Default)]
+struct State {
+    /// The last value written to each register
+    values: BTreeMap<usize, u64>,
+    /// Values to be returned by upcoming reads of each register
+    reads: BTreeMap<usize, VecDeque<u64>>,
+    /// The accesses which are expected to happen, in order
+    expected: VecDeque<MockAccess>,
+    /// Whether any accesses were expected, and so must match
+    strict: bool,
+    log: Vec<MockAccess>,
+}
+
+/// A mock register space
+///
+/// Reads return the next value scripted with [`push_reads()`](Self::push_reads) or
+/// [`expect_read()`](Self::expect_read) for the register, or else the value last written to
+/// it, which is initially zero.
+///
+/// Once any access has been expected, every access must match the next expected access, or
+/// the test panics.
+#[derive(Default)]
+pub struct Mock {
+    state: RefCell<State>,
+}
+
+impl Mock {
+    pub fn new() -> Mock {
+        Self::default()
+    }
+
+    /// Returns a mock register of type `T` at `addr`
+    #[inline]
+    pub fn register<T: MockValue>(&self, addr: usize) -> MockRegister<'_, T> {
+        MockRegister {
+            mock: self,
+            addr,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns a mock register of type `T` at `addr`, with the access `A`
+    #[inline]
+    pub fn register_with_access<T: MockValue, A: access::Access>(
+        &self,
+        addr: usize,
+    ) -> MockRegister<'_, T, A> {
+        MockRegister {
+            mock: self,
+            addr,
+            marker: PhantomData,
+        }
+    }
+
+    /// Sets the value of the register at `addr`, without recording an access
+    pub fn set(&self, addr: usize, value: u64) {
+        self.state.borrow_mut().values.insert(addr, value);
+    }
+
+    /// Returns the value of the register at `addr`, without recording an access
+    pub fn get(&self, addr: usize) -> u64 {
+        self.state.borrow().values.get(&addr).copied().unwrap_or(0)
+    }
+
+    /// Scripts the values returned by the next reads of the register at `addr`
+    pub fn push_reads(&self, addr: usize, values: &[u64]) {
+        let mut state = self.state.borrow_mut();
+        state.reads.entry(addr).or_default().extend(values);
+    }
+
+    /// Expects the next access to be a read of the register at `addr`, which returns `value`
+    pub fn expect_read(&self, addr: usize, value: u64) {
+        self.push_reads(addr, &[value]);
+        self.expect(MockAccess::Read { addr, value });
+    }
+
+    /// Expects the next access to be a write of `value` to the register at `addr`
+    pub fn expect_write(&self, addr: usize, value: u64) {
+        self.expect(MockAccess::Write { addr, value });
+    }
+
+    fn expect(&self, access: MockAccess) {
+        let mut state = self.state.borrow_mut();
+        state.strict = true;
+        state.expected.push_back(access);
+    }
+
+    /// Returns every access made so far, in order
+    pub fn accesses(&self) -> Vec<MockAccess> {
+        self.state.borrow().log.clone()
+    }
+
+    /// Forgets the accesses made so far
+    pub fn clear_accesses(&self) {
+        self.state.borrow_mut().log.clear();
+    }
+
+    /// Asserts that every expected access has happened
+    ///
+    /// # Panics
+    ///
+    /// Panics if any expected accesses are outstanding.
+    #[track_caller]
+    pub fn assert_done(&self) {
+        let state = self.state.borrow();
+        assert!(
+            state.expected.is_empty(),
+            "expected accesses did not happen: {:?}",
+            state.expected,
+        );
+    }
+
+    #[track_caller]
+    fn read(&self, addr: usize) -> u64 {
+        let mut state = self.state.borrow_mut();
+        let scripted = state.reads.get_mut(&addr).and_then(VecDeque::pop_front);
+        let value = scripted.unwrap_or_else(|| state.values.get(&addr).copied().unwrap_or(0));
+        state.values.insert(addr, value);
+        Self::record(&mut state, MockAccess::Read { addr, value });
+        value
+    }
+
+    #[track_caller]
+    fn write(&self, addr: usize, value: u64) {
+        let mut state = self.state.borrow_mut();
+        state.values.insert(addr, value);
+        Self::record(&mut state, MockAccess::Write { addr, value });
+    }
+
+    #[track_caller]
+    fn record(state: &mut State, access: MockAccess) {
+        state.log.push(access);
+        if state.strict {
+            match state.expected.pop_front() {
+                Some(expected) => assert_eq!(access, expected, "unexpected register access"),
+                None => panic!("unexpected register access: {access:?}"),
+            }
+        }
+    }
+}
+
+/// A register of a [`Mock`]
+pub struct MockRegister<'a, T, A = access::ReadWrite> {
+    mock: &'a Mock,
+    addr: usize,
+    marker: PhantomData<(T, A)>,
+}
+
+impl<T, A> MockRegister<'_, T, A> {
+    /// Returns the address of the register
+    pub fn addr(&self) -> usize {
+        self.addr
+    }
+}
+
+impl<T: MockValue, A: Readable> RegisterRead<T> for MockRegister<'_, T, A> {
+    #[track_caller]
+    fn read(&self) -> T {
+        T::from_u64(self.mock.read(self.addr))
+    }
+}
+
+impl<T: MockValue, A: Writable> RegisterWrite<T> for MockRegister<'_, T, A> {
+    #[track_caller]
+    fn write(&self, value: T) {
+        self.mock.write(self.addr, value.to_u64());
+    }
+}
+
+impl<T, A> Clone for MockRegister<'_, T, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, A> Copy for MockRegister<'_, T, A> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripted_reads() {
+        let mock = Mock::new();
+        let reg = mock.register::<u32>(0x10);
+        mock.push_reads(0x10, &[1, 2]);
+        assert_eq!(reg.read(), 1);
+        assert_eq!(reg.read(), 2);
+        assert_eq!(reg.read(), 2);
+        assert_eq!(mock.get(0x10), 2);
+    }
+
+    #[test]
+    fn reads_return_last_write() {
+        let mock = Mock::new();
+        let reg = mock.register::<u16>(0x4);
+        assert_eq!(reg.read(), 0);
+        reg.write(0x1234);
+        assert_eq!(reg.read(), 0x1234);
+        mock.set(0x4, 0x5678);
+        assert_eq!(reg.read(), 0x5678);
+        assert_eq!(
+            mock.accesses(),
+            [
+                MockAccess::Read {
+                    addr: 0x4,
+                    value: 0
+                },
+                MockAccess::Write {
+                    addr: 0x4,
+                    value: 0x1234
+                },
+                MockAccess::Read {
+                    addr: 0x4,
+                    value: 0x1234
+                },
+                MockAccess::Read {
+                    addr: 0x4,
+                    value: 0x5678
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn expected_accesses() {
+        let mock = Mock::new();
+        let reg = mock.register::<u8>(0);
+        mock.expect_read(0, 7);
+        mock.expect_write(0, 8);
+        assert_eq!(reg.read(), 7);
+        reg.write(8);
+        mock.assert_done();
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected register access")]
+    fn expect_write_mismatch() {
+        let mock = Mock::new();
+        mock.expect_write(0, 1);
+        mock.register::<u8>(0).write(2);
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected register access")]
+    fn expect_read_mismatch() {
+        let mock = Mock::new();
+        mock.expect_read(0, 1);
+        mock.register::<u8>(4).read();
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected register access")]
+    fn access_after_expectations() {
+        let mock = Mock::new();
+        let reg = mock.register::<u8>(0);
+        mock.expect_read(0, 1);
+        reg.read();
+        reg.read();
+    }
+
+    #[test]
+    #[should_panic(expected = "expected accesses did not happen")]
+    fn assert_done_outstanding() {
+        let mock = Mock::new();
+        mock.expect_write(0, 1);
+        mock.assert_done();
+    }
+}
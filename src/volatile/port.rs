@@ -0,0 +1,271 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! x86 port-mapped I/O
+//!
+//! Legacy devices such as the 8250/16550 UART, PIT, PIC and CMOS, and the PCI configuration
+//! mechanism #1, are accessed with the `in` and `out` instructions rather than through memory.
+//! [`Port`] implements [`RegisterRead`] and [`RegisterWrite`], so drivers for devices which
+//! may be either port-mapped or memory-mapped can be written once.
+
+use super::{
+    access::{self, Access, Readable, Writable},
+    RegisterRead, RegisterWrite,
+};
+use core::{arch::asm, fmt, marker::PhantomData};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A value which can be transferred with a single port I/O instruction
+///
+/// This trait is sealed and implemented only for `u8`, `u16` and `u32`.
+pub trait PortValue: Copy + private::Sealed {
+    #[doc(hidden)]
+    unsafe fn read_port(port: u16) -> Self;
+    #[doc(hidden)]
+    unsafe fn write_port(port: u16, value: Self);
+    #[doc(hidden)]
+    unsafe fn read_port_string(port: u16, buf: &mut [Self]);
+    #[doc(hidden)]
+    unsafe fn write_port_string(port: u16, buf: &[Self]);
+}
+
+macro_rules! port_values {
+    ($($type:ty: $reg:tt, $ins:literal, $outs:literal;)*) => {$(
+        impl private::Sealed for $type {}
+
+        impl PortValue for $type {
+            #[inline(always)]
+            unsafe fn read_port(port: u16) -> $type {
+                let value;
+                // SAFETY: The caller guarantees that reading the port is safe.
+                unsafe {
+                    asm!(
+                        concat!("in ", $reg, ", dx"),
+                        out($reg) value,
+                        in("dx") port,
+                        options(nomem, nostack, preserves_flags),
+                    );
+                }
+                value
+            }
+
+            #[inline(always)]
+            unsafe fn write_port(port: u16, value: $type) {
+                // SAFETY: The caller guarantees that writing the port is safe. `nomem` is not
+                // given, so that prior writes to memory, such as a DMA descriptor, are not
+                // moved after the write to the port.
+                unsafe {
+                    asm!(
+                        concat!("out dx, ", $reg),
+                        in("dx") port,
+                        in($reg) value,
+                        options(nostack, preserves_flags),
+                    );
+                }
+            }
+
+            #[inline(always)]
+            unsafe fn read_port_string(port: u16, buf: &mut [$type]) {
+                // SAFETY: The caller guarantees that reading the port is safe, and `buf` is
+                // valid for writes of `buf.len()` elements. The direction flag is clear, as
+                // required by the ABI.
+                #[cfg(target_arch = "x86_64")]
+                unsafe {
+                    asm!(
+                        concat!("rep ", $ins),
+                        inout("rdi") buf.as_mut_ptr() => _,
+                        inout("rcx") buf.len() => _,
+                        in("dx") port,
+                        options(nostack, preserves_flags),
+                    );
+                }
+                // SAFETY: See above.
+                #[cfg(target_arch = "x86")]
+                unsafe {
+                    asm!(
+                        concat!("rep ", $ins),
+                        inout("edi") buf.as_mut_ptr() => _,
+                        inout("ecx") buf.len() => _,
+                        in("dx") port,
+                        options(nostack, preserves_flags),
+                    );
+                }
+            }
+
+            #[inline(always)]
+            unsafe fn write_port_string(port: u16, buf: &[$type]) {
+                // SAFETY: The caller guarantees that writing the port is safe, and `buf` is
+                // valid for reads of `buf.len()` elements. The direction flag is clear, as
+                // required by the ABI.
+                #[cfg(target_arch = "x86_64")]
+                unsafe {
+                    asm!(
+                        concat!("rep ", $outs),
+                        inout("rsi") buf.as_ptr() => _,
+                        inout("rcx") buf.len() => _,
+                        in("dx") port,
+                        options(readonly, nostack, preserves_flags),
+                    );
+                }
+                // SAFETY: See above. LLVM reserves `esi` on x86, so it is saved and restored
+                // around the instruction.
+                #[cfg(target_arch = "x86")]
+                unsafe {
+                    asm!(
+                        "xchg {buf}, esi",
+                        concat!("rep ", $outs),
+                        "mov esi, {buf}",
+                        buf = inout(reg) buf.as_ptr() => _,
+                        inout("ecx") buf.len() => _,
+                        in("dx") port,
+                        options(readonly, nostack, preserves_flags),
+                    );
+                }
+            }
+        }
+    )*};
+}
+
+port_values! {
+    u8:  "al",  "insb", "outsb";
+    u16: "ax",  "insw", "outsw";
+    u32: "eax", "insd", "outsd";
+}
+
+/// An I/O port
+///
+/// The access marker `A` determines whether the port may be read, written, or both.
+pub struct Port<T, A = access::ReadWrite> {
+    port: u16,
+    marker: PhantomData<(T, A)>,
+}
+
+/// A port which may only be read
+pub type PortReadOnly<T> = Port<T, access::ReadOnly>;
+
+/// A port which may only be written
+pub type PortWriteOnly<T> = Port<T, access::WriteOnly>;
+
+impl<T: PortValue, A: Access> Port<T, A> {
+    /// Creates a handle to the I/O port `port`
+    ///
+    /// # Safety
+    ///
+    /// Accessing the port with values of type `T` must not violate memory safety, e.g. by
+    /// reprogramming a DMA controller.
+    #[inline]
+    pub const unsafe fn new(port: u16) -> Port<T, A> {
+        Self {
+            port,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the port number
+    #[inline]
+    pub const fn port(&self) -> u16 {
+        self.port
+    }
+
+    #[inline]
+    pub fn read(&self) -> T
+    where
+        A: Readable,
+    {
+        // SAFETY: The creator of the port guaranteed that reading it is safe.
+        unsafe { T::read_port(self.port) }
+    }
+
+    /// Writes `value` to the port
+    ///
+    /// Prior writes to memory are complete before the port is written, so a DMA descriptor
+    /// written before notifying the device needs no [`mmio_wmb()`](super::barrier::mmio_wmb).
+    #[inline]
+    pub fn write(&self, value: T)
+    where
+        A: Writable,
+    {
+        // SAFETY: The creator of the port guaranteed that writing it is safe.
+        unsafe { T::write_port(self.port, value) };
+    }
+
+    /// Reads `buf.len()` values from the port into `buf`, with `rep ins`
+    #[inline]
+    pub fn read_string(&self, buf: &mut [T])
+    where
+        A: Readable,
+    {
+        // SAFETY: The creator of the port guaranteed that reading it is safe.
+        unsafe { T::read_port_string(self.port, buf) };
+    }
+
+    /// Writes the values in `buf` to the port, with `rep outs`
+    #[inline]
+    pub fn write_string(&self, buf: &[T])
+    where
+        A: Writable,
+    {
+        // SAFETY: The creator of the port guaranteed that writing it is safe.
+        unsafe { T::write_port_string(self.port, buf) };
+    }
+}
+
+impl<T: PortValue, A: Readable> RegisterRead<T> for Port<T, A> {
+    #[inline(always)]
+    fn read(&self) -> T {
+        Port::read(self)
+    }
+}
+
+impl<T: PortValue, A: Writable> RegisterWrite<T> for Port<T, A> {
+    #[inline(always)]
+    fn write(&self, value: T) {
+        Port::write(self, value);
+    }
+}
+
+impl<T, A> Clone for Port<T, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, A> Copy for Port<T, A> {}
+
+/// Only the port number is printed, as reading a port may have side effects.
+impl<T, A> fmt::Debug for Port<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Port({:#x})", self.port)
+    }
+}
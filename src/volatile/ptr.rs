@@ -0,0 +1,388 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Volatile access through raw pointers
+
+use super::{
+    access::{self, Access, Readable, Writable},
+    barrier, Timeout,
+};
+use core::{fmt, hint, marker::PhantomData, ops, ptr::NonNull};
+
+/// A pointer providing volatile access to a `T`
+///
+/// Unlike [`Volatile`](super::Volatile), this never creates a reference to the value, so it
+/// may be used for memory-mapped I/O, where the compiler must not be allowed to insert
+/// spurious reads. Fields of a structure are reached with [`map_field!`](crate::map_field!).
+///
+/// The access marker `A` determines whether the value may be read, written, or both.
+#[repr(transparent)]
+pub struct VolatilePtr<'a, T: ?Sized, A = access::ReadWrite> {
+    pointer: NonNull<T>,
+    reference: PhantomData<&'a T>,
+    access: PhantomData<A>,
+}
+
+impl<'a, T: ?Sized> VolatilePtr<'a, T> {
+    /// Creates a volatile pointer to the value at `pointer`
+    ///
+    /// # Safety
+    ///
+    /// `pointer` must be valid for volatile reads and writes of `T` for the lifetime `'a`, and
+    /// must be suitably aligned.
+    #[inline(always)]
+    pub const unsafe fn new(pointer: NonNull<T>) -> VolatilePtr<'a, T> {
+        // SAFETY: The caller upholds our requirements.
+        unsafe { Self::with_access(pointer) }
+    }
+
+    /// Restricts the pointer to reads
+    #[inline(always)]
+    pub const fn read_only(self) -> VolatilePtr<'a, T, access::ReadOnly> {
+        // SAFETY: The pointer was created with the same requirements.
+        unsafe { VolatilePtr::with_access(self.pointer) }
+    }
+
+    /// Restricts the pointer to writes
+    #[inline(always)]
+    pub const fn write_only(self) -> VolatilePtr<'a, T, access::WriteOnly> {
+        // SAFETY: The pointer was created with the same requirements.
+        unsafe { VolatilePtr::with_access(self.pointer) }
+    }
+
+    /// Restricts the pointer to the access `A`
+    #[inline(always)]
+    pub const fn restrict<A: Access>(self) -> VolatilePtr<'a, T, A> {
+        // SAFETY: The pointer was created with the same requirements.
+        unsafe { VolatilePtr::with_access(self.pointer) }
+    }
+}
+
+impl<'a, T: ?Sized, A: Access> VolatilePtr<'a, T, A> {
+    /// Creates a volatile pointer with any access
+    ///
+    /// # Safety
+    ///
+    /// See [`VolatilePtr::new()`].
+    #[inline(always)]
+    pub(crate) const unsafe fn with_access(pointer: NonNull<T>) -> Self {
+        Self {
+            pointer,
+            reference: PhantomData,
+            access: PhantomData,
+        }
+    }
+
+    /// Returns the underlying pointer
+    #[inline(always)]
+    pub const fn as_ptr(self) -> NonNull<T> {
+        self.pointer
+    }
+
+    /// Maps the pointer to another location, such as a field of `T`
+    ///
+    /// # Safety
+    ///
+    /// The pointer returned by `f` must meet the requirements of [`VolatilePtr::new()`].
+    /// In particular, it must stay within the value pointed to by `self`.
+    #[inline(always)]
+    pub unsafe fn map<U: ?Sized>(
+        self,
+        f: impl FnOnce(NonNull<T>) -> NonNull<U>,
+    ) -> VolatilePtr<'a, U, A> {
+        // SAFETY: The caller guarantees that the new pointer is valid.
+        unsafe { VolatilePtr::with_access(f(self.pointer)) }
+    }
+}
+
+impl<T, A: Access> VolatilePtr<'_, T, A> {
+    #[inline]
+    pub fn read(self) -> T
+    where
+        T: Copy,
+        A: Readable,
+    {
+        // SAFETY: The pointer is valid for volatile reads, as guaranteed by the creator.
+        unsafe { self.pointer.as_ptr().read_volatile() }
+    }
+
+    #[inline]
+    pub fn write(self, value: T)
+    where
+        A: Writable,
+    {
+        // SAFETY: The pointer is valid for volatile writes, as guaranteed by the creator.
+        unsafe { self.pointer.as_ptr().write_volatile(value) };
+    }
+
+    /// Reads the value, ordering it before any subsequent memory accesses
+    ///
+    /// See [`barrier::mmio_rmb()`].
+    #[inline]
+    pub fn read_acquire(self) -> T
+    where
+        T: Copy,
+        A: Readable,
+    {
+        let value = self.read();
+        barrier::mmio_rmb();
+        value
+    }
+
+    /// Writes the value, ordering it after any prior memory writes
+    ///
+    /// See [`barrier::mmio_wmb()`].
+    #[inline]
+    pub fn write_release(self, value: T)
+    where
+        A: Writable,
+    {
+        barrier::mmio_wmb();
+        self.write(value);
+    }
+
+    /// Reads the value, and writes back the result of `f`
+    ///
+    /// This is not atomic; the value may change between the read and the write.
+    #[inline]
+    pub fn update(self, f: impl FnOnce(T) -> T)
+    where
+        T: Copy,
+        A: Readable + Writable,
+    {
+        self.write(f(self.read()));
+    }
+
+    /// Sets the bits which are set in `bits`
+    #[inline]
+    pub fn set_bits(self, bits: T)
+    where
+        T: Copy + ops::BitOr<Output = T>,
+        A: Readable + Writable,
+    {
+        self.update(|value| value | bits);
+    }
+
+    /// Clears the bits which are set in `bits`
+    #[inline]
+    pub fn clear_bits(self, bits: T)
+    where
+        T: Copy + ops::BitAnd<Output = T> + ops::Not<Output = T>,
+        A: Readable + Writable,
+    {
+        self.update(|value| value & !bits);
+    }
+
+    /// Replaces the bits which are set in `mask` with those of `value`
+    #[inline]
+    pub fn modify(self, mask: T, value: T)
+    where
+        T: Copy + ops::BitAnd<Output = T> + ops::BitOr<Output = T> + ops::Not<Output = T>,
+        A: Readable + Writable,
+    {
+        self.update(|old| (old & !mask) | (value & mask));
+    }
+
+    /// Reads the value until it satisfies `pred`, returning the value which did
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Timeout`] if the value does not satisfy `pred` after `spin_limit` reads.
+    #[inline]
+    pub fn wait_until(
+        self,
+        mut pred: impl FnMut(T) -> bool,
+        spin_limit: usize,
+    ) -> Result<T, Timeout>
+    where
+        T: Copy,
+        A: Readable,
+    {
+        for _ in 0..spin_limit {
+            let value = self.read();
+            if pred(value) {
+                return Ok(value);
+            }
+            hint::spin_loop();
+        }
+        Err(Timeout)
+    }
+}
+
+impl<T: ?Sized, A> Clone for VolatilePtr<'_, T, A> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized, A> Copy for VolatilePtr<'_, T, A> {}
+
+/// Only the address is printed, as reading device memory may have side effects.
+impl<T: ?Sized, A> fmt::Debug for VolatilePtr<'_, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VolatilePtr").field(&self.pointer).finish()
+    }
+}
+
+/// Projects a [`VolatilePtr`] to a structure onto one of its fields
+///
+/// Fields of `#[repr(packed)]` structures are rejected at compile time, since they may not be
+/// suitably aligned.
+///
+/// ```
+/// use core::ptr::NonNull;
+/// use libsa::volatile::VolatilePtr;
+///
+/// #[repr(C)]
+/// struct Uart {
+///     data: u32,
+///     status: u32,
+/// }
+///
+/// const TX_FULL: u32 = 1 << 5;
+///
+/// let mut regs = Uart { data: 0, status: 0 };
+/// let uart: VolatilePtr<Uart> = unsafe { VolatilePtr::new(NonNull::from(&mut regs)) };
+/// while libsa::map_field!(uart, status).read() & TX_FULL != 0 {}
+/// libsa::map_field!(uart, data).write(b'A' as u32);
+/// assert_eq!(regs.data, b'A' as u32);
+/// ```
+#[macro_export]
+macro_rules! map_field {
+    ($volatile:expr, $($field:tt).+) => {{
+        let volatile = $volatile;
+
+        // Borrowing a field of a packed structure is an error, so this rejects fields which
+        // may be unaligned. It is never executed.
+        #[allow(unreachable_code, clippy::diverging_sub_expression)]
+        if false {
+            // SAFETY: This is never executed.
+            let _ = &(unsafe { &*volatile.as_ptr().as_ptr() }).$($field).+;
+        }
+
+        // SAFETY: The field lies within the structure, so the pointer to it is valid for as
+        // long as the pointer to the structure is, and we've checked it is aligned.
+        unsafe {
+            volatile.map(|ptr| {
+                ::core::ptr::NonNull::new_unchecked(
+                    ::core::ptr::addr_of_mut!((*ptr.as_ptr()).$($field).+),
+                )
+            })
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(C)]
+    struct Regs {
+        ctrl: u32,
+        fifo: [u16; 2],
+        inner: Inner,
+    }
+
+    #[repr(C)]
+    struct Inner {
+        status: u8,
+        data: u64,
+    }
+
+    #[test]
+    fn field_projection() {
+        let mut regs = Regs {
+            ctrl: 1,
+            fifo: [2, 3],
+            inner: Inner { status: 4, data: 5 },
+        };
+        // SAFETY: `regs` outlives the pointer.
+        let ptr = unsafe { VolatilePtr::new(NonNull::from(&mut regs)) };
+        assert_eq!(crate::map_field!(ptr, ctrl).read(), 1);
+        assert_eq!(crate::map_field!(ptr, inner.status).read(), 4);
+        crate::map_field!(ptr, inner.data).write(6);
+        crate::map_field!(ptr, fifo).write([7, 8]);
+        assert_eq!(
+            crate::map_field!(ptr, inner).as_ptr().as_ptr(),
+            core::ptr::addr_of_mut!(regs.inner)
+        );
+        assert_eq!(regs.inner.data, 6);
+        assert_eq!(regs.fifo, [7, 8]);
+    }
+
+    #[test]
+    fn restricted_access() {
+        let mut value = 1_u32;
+        // SAFETY: `value` outlives the pointer.
+        let ptr = unsafe { VolatilePtr::new(NonNull::from(&mut value)) };
+        assert_eq!(ptr.read_only().read(), 1);
+        ptr.write_only().write(2);
+        assert_eq!(ptr.restrict::<access::ReadOnly>().read(), 2);
+        assert_eq!(ptr.write_only().as_ptr(), ptr.as_ptr());
+        assert_eq!(value, 2);
+    }
+
+    #[test]
+    fn read_modify_write() {
+        let mut value = 0x00f0_u32;
+        // SAFETY: `value` outlives the pointer.
+        let ptr = unsafe { VolatilePtr::new(NonNull::from(&mut value)) };
+        ptr.update(|value| value + 1);
+        assert_eq!(ptr.read(), 0x00f1);
+        ptr.set_bits(0x0f00);
+        assert_eq!(ptr.read(), 0x0ff1);
+        ptr.clear_bits(0x00f1);
+        assert_eq!(ptr.read(), 0x0f00);
+        ptr.modify(0x0ff0, 0x1234);
+        assert_eq!(ptr.read(), 0x0230);
+        assert_eq!(value, 0x0230);
+    }
+
+    #[test]
+    fn wait_until() {
+        let mut value = 5_u8;
+        // SAFETY: `value` outlives the pointer.
+        let ptr = unsafe { VolatilePtr::new(NonNull::from(&mut value)) };
+        assert_eq!(ptr.wait_until(|value| value == 5, 1), Ok(5));
+
+        let mut reads = 0;
+        let result = ptr.wait_until(
+            |_| {
+                reads += 1;
+                false
+            },
+            10,
+        );
+        assert_eq!(result, Err(Timeout));
+        assert_eq!(reads, 10);
+        assert_eq!(ptr.wait_until(|_| true, 0), Err(Timeout));
+    }
+}
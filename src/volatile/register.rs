@@ -0,0 +1,169 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Traits abstracting over register access
+//!
+//! Driver code written against [`RegisterRead`] and [`RegisterWrite`] rather than a concrete
+//! type can be used with any kind of register, including the mock registers provided by the
+//! `mock` feature when testing on the host.
+
+use super::{
+    access::{Readable, Writable},
+    Timeout, Volatile, VolatileEndian, VolatilePtr,
+};
+use crate::endian::{ByteOrder, Primitive};
+use core::{hint, ops};
+
+/// A register which can be read
+pub trait RegisterRead<T> {
+    fn read(&self) -> T;
+
+    /// Reads the register until it satisfies `pred`, returning the value which did
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Timeout`] if the value does not satisfy `pred` after `spin_limit` reads.
+    #[inline]
+    fn wait_until(&self, mut pred: impl FnMut(T) -> bool, spin_limit: usize) -> Result<T, Timeout>
+    where
+        T: Copy,
+    {
+        for _ in 0..spin_limit {
+            let value = self.read();
+            if pred(value) {
+                return Ok(value);
+            }
+            hint::spin_loop();
+        }
+        Err(Timeout)
+    }
+}
+
+/// A register which can be written
+pub trait RegisterWrite<T> {
+    fn write(&self, value: T);
+}
+
+/// A register which can be read and written
+///
+/// This is implemented for every type which implements both [`RegisterRead`] and
+/// [`RegisterWrite`].
+pub trait Register<T>: RegisterRead<T> + RegisterWrite<T> {
+    /// Reads the register, and writes back the result of `f`
+    #[inline]
+    fn update(&self, f: impl FnOnce(T) -> T) {
+        self.write(f(self.read()));
+    }
+
+    /// Sets the bits which are set in `bits`
+    #[inline]
+    fn set_bits(&self, bits: T)
+    where
+        T: ops::BitOr<Output = T>,
+    {
+        self.update(|value| value | bits);
+    }
+
+    /// Clears the bits which are set in `bits`
+    #[inline]
+    fn clear_bits(&self, bits: T)
+    where
+        T: ops::BitAnd<Output = T> + ops::Not<Output = T>,
+    {
+        self.update(|value| value & !bits);
+    }
+
+    /// Replaces the bits which are set in `mask` with those of `value`
+    #[inline]
+    fn modify(&self, mask: T, value: T)
+    where
+        T: Copy + ops::BitAnd<Output = T> + ops::BitOr<Output = T> + ops::Not<Output = T>,
+    {
+        self.update(|old| (old & !mask) | (value & mask));
+    }
+}
+
+impl<T, R: RegisterRead<T> + RegisterWrite<T> + ?Sized> Register<T> for R {}
+
+impl<T, A: Readable> RegisterRead<T> for Volatile<T, A> {
+    #[inline(always)]
+    fn read(&self) -> T {
+        Volatile::read(self)
+    }
+}
+
+impl<T, A: Writable> RegisterWrite<T> for Volatile<T, A> {
+    #[inline(always)]
+    fn write(&self, value: T) {
+        Volatile::write(self, value);
+    }
+}
+
+impl<T: Copy, A: Readable> RegisterRead<T> for VolatilePtr<'_, T, A> {
+    #[inline(always)]
+    fn read(&self) -> T {
+        VolatilePtr::read(*self)
+    }
+}
+
+impl<T, A: Writable> RegisterWrite<T> for VolatilePtr<'_, T, A> {
+    #[inline(always)]
+    fn write(&self, value: T) {
+        VolatilePtr::write(*self, value);
+    }
+}
+
+impl<T: Primitive, O: ByteOrder, A: Readable> RegisterRead<T> for VolatileEndian<T, O, A> {
+    #[inline(always)]
+    fn read(&self) -> T {
+        VolatileEndian::read(self)
+    }
+}
+
+impl<T: Primitive, O: ByteOrder, A: Writable> RegisterWrite<T> for VolatileEndian<T, O, A> {
+    #[inline(always)]
+    fn write(&self, value: T) {
+        VolatileEndian::write(self, value);
+    }
+}
+
+impl<T, R: RegisterRead<T> + ?Sized> RegisterRead<T> for &R {
+    #[inline(always)]
+    fn read(&self) -> T {
+        R::read(self)
+    }
+}
+
+impl<T, R: RegisterWrite<T> + ?Sized> RegisterWrite<T> for &R {
+    #[inline(always)]
+    fn write(&self, value: T) {
+        R::write(self, value);
+    }
+}
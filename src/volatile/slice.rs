@@ -0,0 +1,444 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Volatile arrays and slices
+//!
+//! Reading or writing a whole `Volatile<[T; N]>` copies the entire array with a single volatile
+//! access, which devices generally do not support. These types instead access each element
+//! individually, with the element's own width.
+
+use super::{
+    access::{self, Access, Readable, Writable},
+    Volatile, VolatilePtr,
+};
+use core::{fmt, iter::FusedIterator, ops, ptr::NonNull};
+
+/// A volatile pointer to a slice, such as a descriptor ring or framebuffer
+///
+/// Each element is accessed individually, through the [`VolatilePtr`] returned by
+/// [`index()`](VolatilePtr::index) or [`iter()`](VolatilePtr::iter).
+pub type VolatileSlice<'a, T, A = access::ReadWrite> = VolatilePtr<'a, [T], A>;
+
+impl<'a, T, A: Access> VolatilePtr<'a, [T], A> {
+    /// Returns the number of elements in the slice
+    #[inline]
+    pub fn len(self) -> usize {
+        self.as_ptr().len()
+    }
+
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a pointer to the element at `index`, or `None` if it is out of bounds
+    #[inline]
+    pub fn get(self, index: usize) -> Option<VolatilePtr<'a, T, A>> {
+        if index < self.len() {
+            // SAFETY: We've just checked that `index` is in bounds.
+            Some(unsafe { self.get_unchecked(index) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns a pointer to the element at `index`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    #[track_caller]
+    pub fn index(self, index: usize) -> VolatilePtr<'a, T, A> {
+        let len = self.len();
+        match self.get(index) {
+            Some(element) => element,
+            None => panic!("index out of bounds: the len is {len} but the index is {index}"),
+        }
+    }
+
+    /// Returns a pointer to the element at `index`, without bounds checking
+    ///
+    /// # Safety
+    ///
+    /// `index` must be less than the length of the slice.
+    #[inline]
+    pub unsafe fn get_unchecked(self, index: usize) -> VolatilePtr<'a, T, A> {
+        // SAFETY: The caller guarantees that `index` is in bounds, so the element lies within
+        // the slice.
+        unsafe { self.map(|slice| slice.cast::<T>().add(index)) }
+    }
+
+    /// Returns a pointer to the elements in `range`, or `None` if it is out of bounds
+    #[inline]
+    pub fn subslice(self, range: ops::Range<usize>) -> Option<VolatileSlice<'a, T, A>> {
+        if range.start > range.end || range.end > self.len() {
+            return None;
+        }
+        // SAFETY: We've just checked that the range lies within the slice.
+        Some(unsafe {
+            self.map(|slice| {
+                NonNull::slice_from_raw_parts(slice.cast::<T>().add(range.start), range.len())
+            })
+        })
+    }
+
+    /// Returns an iterator over pointers to each element
+    #[inline]
+    pub fn iter(self) -> Iter<'a, T, A> {
+        Iter {
+            range: 0..self.len(),
+            slice: self,
+        }
+    }
+
+    /// Copies the elements of `src` into the slice, one element at a time
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` has a different length than the slice.
+    #[inline]
+    #[track_caller]
+    pub fn copy_from_slice(self, src: &[T])
+    where
+        T: Copy,
+        A: Writable,
+    {
+        assert_eq!(
+            self.len(),
+            src.len(),
+            "source and destination have different lengths"
+        );
+        for (dst, src) in self.iter().zip(src) {
+            dst.write(*src);
+        }
+    }
+
+    /// Copies the elements of the slice into `dst`, one element at a time
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` has a different length than the slice.
+    #[inline]
+    #[track_caller]
+    pub fn copy_into_slice(self, dst: &mut [T])
+    where
+        T: Copy,
+        A: Readable,
+    {
+        assert_eq!(
+            self.len(),
+            dst.len(),
+            "source and destination have different lengths"
+        );
+        for (src, dst) in self.iter().zip(dst) {
+            *dst = src.read();
+        }
+    }
+
+    /// Writes `value` to every element
+    #[inline]
+    pub fn fill(self, value: T)
+    where
+        T: Copy,
+        A: Writable,
+    {
+        for element in self.iter() {
+            element.write(value);
+        }
+    }
+}
+
+impl<'a, T, const N: usize, A: Access> VolatilePtr<'a, [T; N], A> {
+    /// Converts the pointer to an array into a pointer to a slice
+    #[inline]
+    pub fn as_slice(self) -> VolatileSlice<'a, T, A> {
+        // SAFETY: The slice covers the same memory as the array.
+        unsafe { self.map(|array| NonNull::slice_from_raw_parts(array.cast::<T>(), N)) }
+    }
+}
+
+/// An iterator over pointers to the elements of a [`VolatileSlice`]
+///
+/// This is created by [`VolatilePtr::iter()`].
+pub struct Iter<'a, T, A = access::ReadWrite> {
+    slice: VolatileSlice<'a, T, A>,
+    range: ops::Range<usize>,
+}
+
+impl<'a, T, A: Access> Iterator for Iter<'a, T, A> {
+    type Item = VolatilePtr<'a, T, A>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.range.next()?;
+        // SAFETY: The range is within the bounds of the slice.
+        Some(unsafe { self.slice.get_unchecked(index) })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let index = self.range.nth(n)?;
+        // SAFETY: The range is within the bounds of the slice.
+        Some(unsafe { self.slice.get_unchecked(index) })
+    }
+}
+
+impl<T, A: Access> DoubleEndedIterator for Iter<'_, T, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let index = self.range.next_back()?;
+        // SAFETY: The range is within the bounds of the slice.
+        Some(unsafe { self.slice.get_unchecked(index) })
+    }
+}
+
+impl<T, A: Access> ExactSizeIterator for Iter<'_, T, A> {}
+
+impl<T, A: Access> FusedIterator for Iter<'_, T, A> {}
+
+impl<T, A> Clone for Iter<'_, T, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            slice: self.slice,
+            range: self.range.clone(),
+        }
+    }
+}
+
+impl<T, A> fmt::Debug for Iter<'_, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Iter")
+            .field("slice", &self.slice)
+            .field("range", &self.range)
+            .finish()
+    }
+}
+
+/// An array of values which are each accessed individually
+///
+/// This has the same layout as `[T; N]`, and may be embedded in structures in place of a
+/// `Volatile<[T; N]>`, such as an MSI-X table.
+#[repr(transparent)]
+pub struct VolatileArray<T, const N: usize, A = access::ReadWrite> {
+    elements: [Volatile<T, A>; N],
+}
+
+impl<T, const N: usize> VolatileArray<T, N> {
+    #[inline]
+    pub fn new(values: [T; N]) -> VolatileArray<T, N> {
+        Self::with_access(values)
+    }
+}
+
+impl<T, const N: usize, A: Access> VolatileArray<T, N, A> {
+    /// Creates an array with the access marker `A`
+    #[inline]
+    pub fn with_access(values: [T; N]) -> VolatileArray<T, N, A> {
+        Self {
+            elements: values.map(Volatile::with_access),
+        }
+    }
+
+    #[inline]
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Returns the element at `index`, or `None` if it is out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&Volatile<T, A>> {
+        self.elements.get(index)
+    }
+
+    /// Returns a [`VolatileSlice`] of the elements
+    #[inline]
+    pub fn as_slice(&self) -> VolatileSlice<'_, T, A> {
+        let len = self.elements.len();
+        let ptr = NonNull::from(&self.elements).cast::<T>();
+        // SAFETY: `Volatile<T, A>` is a transparent wrapper around `UnsafeCell<T>`, so the
+        // elements may be accessed through this pointer while `self` is borrowed.
+        unsafe { VolatilePtr::with_access(NonNull::slice_from_raw_parts(ptr, len)) }
+    }
+
+    /// Returns an iterator over the elements
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, Volatile<T, A>> {
+        self.elements.iter()
+    }
+
+    /// Copies the elements of `src` into the array, one element at a time
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` has a different length than the array.
+    #[inline]
+    #[track_caller]
+    pub fn copy_from_slice(&self, src: &[T])
+    where
+        T: Copy,
+        A: Writable,
+    {
+        self.as_slice().copy_from_slice(src);
+    }
+
+    /// Copies the elements of the array into `dst`, one element at a time
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` has a different length than the array.
+    #[inline]
+    #[track_caller]
+    pub fn copy_into_slice(&self, dst: &mut [T])
+    where
+        T: Copy,
+        A: Readable,
+    {
+        self.as_slice().copy_into_slice(dst);
+    }
+
+    /// Writes `value` to every element
+    #[inline]
+    pub fn fill(&self, value: T)
+    where
+        T: Copy,
+        A: Writable,
+    {
+        self.as_slice().fill(value);
+    }
+}
+
+impl<T, const N: usize, A> ops::Index<usize> for VolatileArray<T, N, A> {
+    type Output = Volatile<T, A>;
+
+    #[inline]
+    #[track_caller]
+    fn index(&self, index: usize) -> &Volatile<T, A> {
+        &self.elements[index]
+    }
+}
+
+impl<'a, T, const N: usize, A> IntoIterator for &'a VolatileArray<T, N, A> {
+    type Item = &'a Volatile<T, A>;
+    type IntoIter = core::slice::Iter<'a, Volatile<T, A>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
+impl<T, const N: usize, A> fmt::Debug for VolatileArray<T, N, A>
+where
+    Volatile<T, A>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.elements).finish()
+    }
+}
+
+impl<T: Default, const N: usize, A: Access> Default for VolatileArray<T, N, A> {
+    fn default() -> Self {
+        Self {
+            elements: core::array::from_fn(|_| Volatile::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds() {
+        let array = VolatileArray::new([1_u16, 2, 3, 4]);
+        let slice = array.as_slice();
+        assert_eq!(slice.len(), 4);
+        assert_eq!(slice.index(3).read(), 4);
+        assert!(slice.get(4).is_none());
+        assert!(array.get(4).is_none());
+
+        let sub = slice.subslice(1..3).unwrap();
+        assert_eq!(sub.len(), 2);
+        assert_eq!(sub.index(0).read(), 2);
+        assert!(sub.get(2).is_none());
+        assert!(slice.subslice(2..5).is_none());
+        assert!(slice.subslice(5..5).is_none());
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = slice.subslice(3..2);
+        assert!(reversed.is_none());
+        assert!(slice.subslice(4..4).unwrap().is_empty());
+    }
+
+    #[test]
+    #[should_panic = "index out of bounds: the len is 2 but the index is 2"]
+    fn index_out_of_bounds() {
+        let array = VolatileArray::new([0_u32; 4]);
+        array.as_slice().subslice(2..4).unwrap().index(2);
+    }
+
+    #[test]
+    fn copy_and_fill() {
+        let array = VolatileArray::new([0_u8; 4]);
+        array.copy_from_slice(&[1, 2, 3, 4]);
+        array.as_slice().subslice(1..3).unwrap().fill(9);
+        let mut values = [0; 4];
+        array.copy_into_slice(&mut values);
+        assert_eq!(values, [1, 9, 9, 4]);
+        assert_eq!(array.iter().map(Volatile::read).collect::<Vec<_>>(), values);
+        array.fill(5);
+        assert_eq!(array[2].read(), 5);
+    }
+
+    #[test]
+    #[should_panic = "source and destination have different lengths"]
+    fn copy_from_slice_length_mismatch() {
+        let array = VolatileArray::new([0_u8; 4]);
+        array.copy_from_slice(&[1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic = "source and destination have different lengths"]
+    fn copy_into_slice_length_mismatch() {
+        let array = VolatileArray::new([0_u8; 4]);
+        array.copy_into_slice(&mut [0; 5]);
+    }
+}
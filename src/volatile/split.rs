@@ -0,0 +1,342 @@
+/*
+ * Copyright (c) 2022 xvanc <xvancm@gmail.com>
+ *
+ * Redistribution and use in source and binary forms, with or without modification,
+ * are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its contributors
+ *    may be used to endorse or promote products derived from this software without
+ *    specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY
+ * EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES
+ * OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED.
+ * IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT,
+ * INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+ * PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT
+ * LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ *
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! 64-bit values split across two 32-bit registers
+//!
+//! Many devices only support 32-bit accesses, so 64-bit registers are accessed as two halves.
+//! A value which the device updates on its own, such as a counter, may change between the two
+//! accesses; [`VolatileSplitU64::read_consistent()`] guards against this.
+
+use super::{RegisterRead, RegisterWrite, VolatileEndian};
+use crate::endian::{ByteOrder, NativeEndian};
+use core::{fmt, marker::PhantomData};
+
+/// The order in which the halves of a split value are written
+///
+/// Some devices latch the value on the write of a particular half, and so require the other
+/// half to be written first.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SplitOrder {
+    /// Write the low half, then the high half
+    #[default]
+    LowFirst,
+    /// Write the high half, then the low half
+    HighFirst,
+}
+
+/// A 64-bit value, of which its low and high bits are split in 32-bit-aligned volatile memory
+///
+/// Each half is stored in `O` byte order, with the low half first.
+#[repr(C)]
+#[derive(Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct VolatileSplitU64<O: ByteOrder = NativeEndian> {
+    low: VolatileEndian<u32, O>,
+    high: VolatileEndian<u32, O>,
+}
+
+impl<O: ByteOrder> fmt::Debug for VolatileSplitU64<O> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.get(), f)
+    }
+}
+
+impl<O: ByteOrder> VolatileSplitU64<O> {
+    /// Gets the contained value
+    ///
+    /// The halves are read independently, so if the value changes between the two reads the
+    /// result may be torn. See [`read_consistent()`](Self::read_consistent).
+    #[inline]
+    pub fn get(&self) -> u64 {
+        let low = self.low.read();
+        let high = self.high.read();
+
+        ((high as u64) << 32) | (low as u64)
+    }
+
+    /// Gets the contained value, ensuring the halves belong to the same value
+    ///
+    /// The high half is read before and after the low half, and the read is retried if it
+    /// changed. This is suitable for counters which the device increments on its own.
+    #[inline]
+    pub fn read_consistent(&self) -> u64 {
+        read_consistent(&self.high, &self.low)
+    }
+
+    /// Gets the component parts of the contained value, as `(high, low)`
+    #[inline]
+    pub fn get_parts(&self) -> (u32, u32) {
+        (self.high.read(), self.low.read())
+    }
+
+    /// Sets the contained value, writing the low half first
+    #[inline]
+    pub fn set(&self, value: u64) {
+        self.set_ordered(value, SplitOrder::LowFirst);
+    }
+
+    /// Sets the contained value, writing the halves in `order`
+    #[inline]
+    pub fn set_ordered(&self, value: u64, order: SplitOrder) {
+        #![allow(clippy::cast_possible_truncation)]
+        self.set_parts_ordered((value >> 32) as u32, value as u32, order);
+    }
+
+    /// Sets the component parts of the contained value, writing the low half first
+    #[inline]
+    pub fn set_parts(&self, high: u32, low: u32) {
+        self.set_parts_ordered(high, low, SplitOrder::LowFirst);
+    }
+
+    /// Sets the component parts of the contained value, writing the halves in `order`
+    #[inline]
+    pub fn set_parts_ordered(&self, high: u32, low: u32, order: SplitOrder) {
+        write_parts(&self.high, &self.low, high, low, order);
+    }
+}
+
+/// Reads a value split across `high` and `low`, retrying if `high` changes in between
+#[inline]
+fn read_consistent(high: &impl RegisterRead<u32>, low: &impl RegisterRead<u32>) -> u64 {
+    let mut upper = high.read();
+    loop {
+        let lower = low.read();
+        let next = high.read();
+        if next == upper {
+            return ((upper as u64) << 32) | (lower as u64);
+        }
+        upper = next;
+    }
+}
+
+/// Writes the halves of a split value in `order`
+#[inline]
+fn write_parts(
+    high: &impl RegisterWrite<u32>,
+    low: &impl RegisterWrite<u32>,
+    upper: u32,
+    lower: u32,
+    order: SplitOrder,
+) {
+    match order {
+        SplitOrder::LowFirst => {
+            low.write(lower);
+            high.write(upper);
+        }
+        SplitOrder::HighFirst => {
+            high.write(upper);
+            low.write(lower);
+        }
+    }
+}
+
+/// An error returned when an address does not fit in a pointer on the target
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AddressOutOfRange;
+
+impl fmt::Display for AddressOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("address does not fit in a pointer")
+    }
+}
+
+/// Represents a 64 bit pointer, of which its low and high bits are split in 32-bit-aligned
+/// volatile memory.
+///
+/// Each half is stored in `O` byte order, with the low half first.
+///
+/// The value is usually a physical address used for DMA, which may be wider than a pointer on
+/// 32-bit targets, so it is returned as a `u64`. [`as_ptr()`](Self::as_ptr) converts it to a
+/// pointer if it fits.
+#[repr(C)]
+#[derive(Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct VolatileSplitPtr<T, O: ByteOrder = NativeEndian> {
+    value: VolatileSplitU64<O>,
+    marker: PhantomData<T>,
+}
+
+impl<T, O: ByteOrder> fmt::Debug for VolatileSplitPtr<T, O> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}", self.get())
+    }
+}
+
+impl<T, O: ByteOrder> VolatileSplitPtr<T, O> {
+    /// Gets the contained address
+    #[inline]
+    pub fn get(&self) -> u64 {
+        self.value.get()
+    }
+
+    /// Gets the contained address, ensuring the halves belong to the same value
+    ///
+    /// See [`VolatileSplitU64::read_consistent()`].
+    #[inline]
+    pub fn read_consistent(&self) -> u64 {
+        self.value.read_consistent()
+    }
+
+    /// Gets the contained address as a pointer
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AddressOutOfRange`] if the address does not fit in a pointer, which is only
+    /// possible on targets with pointers narrower than 64 bits.
+    #[inline]
+    pub fn as_ptr(&self) -> Result<*mut T, AddressOutOfRange> {
+        match usize::try_from(self.get()) {
+            Ok(addr) => Ok(addr as *mut T),
+            Err(_) => Err(AddressOutOfRange),
+        }
+    }
+
+    /// Sets the contained address
+    #[inline]
+    pub fn set_addr(&self, addr: u64) {
+        self.value.set(addr);
+    }
+
+    /// Sets the contained address, writing the halves in `order`
+    #[inline]
+    pub fn set_addr_ordered(&self, addr: u64, order: SplitOrder) {
+        self.value.set_ordered(addr, order);
+    }
+
+    /// Sets the contained pointer
+    #[inline]
+    pub fn set(&self, ptr: *mut T) {
+        self.value.set(ptr as u64);
+    }
+
+    /// Sets the contained pointer, writing the halves in `order`
+    #[inline]
+    pub fn set_ordered(&self, ptr: *mut T, order: SplitOrder) {
+        self.value.set_ordered(ptr as u64, order);
+    }
+
+    /// Sets the component parts of the contained pointer
+    #[inline]
+    pub fn set_parts(&self, high: u32, low: u32) {
+        self.value.set_parts(high, low);
+    }
+
+    /// Sets the component parts of the contained pointer, writing the halves in `order`
+    #[inline]
+    pub fn set_parts_ordered(&self, high: u32, low: u32, order: SplitOrder) {
+        self.value.set_parts_ordered(high, low, order);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endian::BigEndian;
+
+    #[test]
+    fn read_consistent_no_carry() {
+        let value = VolatileSplitU64::<BigEndian>::default();
+        value.set(0x0000_0001_ffff_fffe);
+        assert_eq!(value.read_consistent(), 0x0000_0001_ffff_fffe);
+        assert_eq!(value.get_parts(), (0x0000_0001, 0xffff_fffe));
+
+        let ptr = VolatileSplitPtr::<u8>::default();
+        ptr.set_addr(0x1234_5678_9abc_def0);
+        assert_eq!(ptr.read_consistent(), 0x1234_5678_9abc_def0);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn read_consistent_retries_on_carry() {
+        use crate::volatile::mock::{Mock, MockAccess};
+
+        let mock = Mock::new();
+        let low = mock.register::<u32>(0x0);
+        let high = mock.register::<u32>(0x4);
+        // The counter rolls over from 0x1_ffff_ffff to 0x2_0000_0000 between the first two
+        // reads of the high half.
+        mock.push_reads(0x4, &[0x1, 0x2, 0x2]);
+        mock.push_reads(0x0, &[0xffff_ffff, 0x0000_0003]);
+        assert_eq!(read_consistent(&high, &low), 0x0000_0002_0000_0003);
+        assert_eq!(
+            mock.accesses(),
+            [
+                MockAccess::Read {
+                    addr: 0x4,
+                    value: 0x1
+                },
+                MockAccess::Read {
+                    addr: 0x0,
+                    value: 0xffff_ffff
+                },
+                MockAccess::Read {
+                    addr: 0x4,
+                    value: 0x2
+                },
+                MockAccess::Read {
+                    addr: 0x0,
+                    value: 0x3
+                },
+                MockAccess::Read {
+                    addr: 0x4,
+                    value: 0x2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ordered_writes() {
+        let ptr = VolatileSplitPtr::<u8>::default();
+        ptr.set_addr_ordered(0x0000_0001_0000_1000, SplitOrder::HighFirst);
+        assert_eq!(ptr.get(), 0x0000_0001_0000_1000);
+        ptr.set_parts_ordered(0x2, 0x2000, SplitOrder::LowFirst);
+        assert_eq!(ptr.get(), 0x0000_0002_0000_2000);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn write_order() {
+        use crate::volatile::mock::Mock;
+
+        let mock = Mock::new();
+        let low = mock.register::<u32>(0x0);
+        let high = mock.register::<u32>(0x4);
+        mock.expect_write(0x0, 0x1000);
+        mock.expect_write(0x4, 0x1);
+        mock.expect_write(0x4, 0x2);
+        mock.expect_write(0x0, 0x2000);
+        write_parts(&high, &low, 0x1, 0x1000, SplitOrder::LowFirst);
+        write_parts(&high, &low, 0x2, 0x2000, SplitOrder::HighFirst);
+        mock.assert_done();
+    }
+}